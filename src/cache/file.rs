@@ -0,0 +1,218 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{CacheBackend, CacheKey, CachedResult};
+
+/// A cache that stores each search result as a JSON file in a directory, so results survive restarts.
+///
+/// Each file records when the result was fetched; lifetimes are checked when the result is read,
+/// so changing the TTL also applies to results cached by earlier runs.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use sia_rs::{FileCache, SiaClient};
+///
+/// let cache = FileCache::new("/var/cache/sia")
+///     .unwrap()
+///     .with_ttl(Duration::from_secs(24 * 60 * 60));
+/// let client = SiaClient::builder().cache(cache).build().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+    ttl: Duration,
+    not_found_ttl: Duration,
+}
+
+/// The contents of a single cache file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    key: CacheKey,
+    fetched_at: DateTime<Utc>,
+    result: CachedResult,
+}
+
+/// FNV-1a, used instead of the std hasher because file names must stay stable between builds.
+fn stable_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl FileCache {
+    /// Opens a cache in the given directory, creating it if needed. Found results are kept for a day
+    /// and not-found results for an hour.
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            ttl: Duration::from_secs(24 * 60 * 60),
+            not_found_ttl: Duration::from_secs(60 * 60),
+        })
+    }
+
+    /// Sets how long found results are kept.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long "no licenses found" results are kept.
+    pub fn with_not_found_ttl(mut self, ttl: Duration) -> Self {
+        self.not_found_ttl = ttl;
+        self
+    }
+
+    /// The directory results are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path_for(&self, key: &CacheKey) -> PathBuf {
+        let name = match key {
+            CacheKey::License(license_no) => format!("license-{}.json", license_no),
+            CacheKey::Name(fields) => format!("name-{:016x}.json", stable_hash(&fields.join("\n"))),
+        };
+        self.dir.join(name)
+    }
+
+    fn read(&self, path: &Path) -> Option<Record> {
+        let contents = fs::read(path).ok()?;
+        match serde_json::from_slice(&contents) {
+            Ok(record) => Some(record),
+            Err(err) => {
+                warn!("Ignoring unreadable cache file {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    fn records(&self) -> Vec<(PathBuf, Record)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| self.read(&path).map(|record| (path, record)))
+            .collect()
+    }
+
+    /// The time the cached result for a key was fetched, if there is one.
+    pub fn fetched_at(&self, key: &CacheKey) -> Option<DateTime<Utc>> {
+        self.read(&self.path_for(key))
+            .filter(|record| record.key == *key)
+            .map(|record| record.fetched_at)
+    }
+}
+
+impl CacheBackend for FileCache {
+    fn get(&self, key: &CacheKey) -> Option<CachedResult> {
+        let path = self.path_for(key);
+        let record = self.read(&path).filter(|record| record.key == *key)?;
+
+        let ttl = match record.result {
+            CachedResult::Found(_) => self.ttl,
+            CachedResult::NotFound => self.not_found_ttl,
+        };
+        let age = (Utc::now() - record.fetched_at)
+            .to_std()
+            .unwrap_or_default();
+        if age >= ttl {
+            let _ = fs::remove_file(path);
+            return None;
+        }
+
+        Some(record.result)
+    }
+
+    fn insert(&self, key: CacheKey, result: CachedResult) {
+        let path = self.path_for(&key);
+        let record = Record {
+            key,
+            fetched_at: Utc::now(),
+            result,
+        };
+
+        // Write to a temporary file first so a crash never leaves a half-written entry behind.
+        let tmp = path.with_extension("tmp");
+        let written = serde_json::to_vec(&record)
+            .map_err(io::Error::from)
+            .and_then(|contents| fs::write(&tmp, contents))
+            .and_then(|_| fs::rename(&tmp, &path));
+        if let Err(err) = written {
+            warn!("Unable to write cache file {:?}: {}", path, err);
+        }
+    }
+
+    fn invalidate_license(&self, license_no: &str) {
+        let direct = CacheKey::license(license_no);
+        for (path, record) in self.records() {
+            if record.key == direct || record.result.contains_license(license_no) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        for (path, _) in self.records() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sia_rs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_results_survive_reopening() {
+        let dir = temp_dir("reopen");
+        let key = CacheKey::license("1234567890123456");
+
+        FileCache::new(&dir)
+            .unwrap()
+            .insert(key.clone(), CachedResult::NotFound);
+
+        let cache = FileCache::new(&dir).unwrap();
+        assert_eq!(cache.get(&key), Some(CachedResult::NotFound));
+        assert!(cache.fetched_at(&key).is_some());
+
+        cache.invalidate_license("1234 5678 9012 3456");
+        assert_eq!(cache.get(&key), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_expired_results_are_ignored() {
+        let dir = temp_dir("expiry");
+        let cache = FileCache::new(&dir)
+            .unwrap()
+            .with_not_found_ttl(Duration::ZERO);
+        let key = CacheKey::license("1234567890123456");
+
+        cache.insert(key.clone(), CachedResult::NotFound);
+        assert_eq!(cache.get(&key), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
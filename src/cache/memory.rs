@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::cache::{CacheBackend, CacheKey, CachedResult};
+
+/// An in-memory cache of search results with separate lifetimes for found and not-found results.
+/// Once full, the least recently used entry is evicted.
+///
+/// Cloning the cache shares its entries, so a handle can be kept for invalidation after passing it to a client.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use sia_rs::{CacheBackend, MemoryCache, SiaClient};
+///
+/// let cache = MemoryCache::new(1_000)
+///     .with_ttl(Duration::from_secs(3600))
+///     .with_not_found_ttl(Duration::from_secs(300));
+/// let client = SiaClient::builder().cache(cache.clone()).build().unwrap();
+///
+/// cache.invalidate_license("1234567890123456");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryCache {
+    inner: Arc<Mutex<Inner>>,
+    ttl: Duration,
+    not_found_ttl: Duration,
+}
+
+#[derive(Debug)]
+struct Entry {
+    result: CachedResult,
+    expires: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug)]
+struct Inner {
+    max_entries: usize,
+    entries: HashMap<CacheKey, Entry>,
+    /// Keys ordered by when they were last used, oldest first.
+    recency: BTreeMap<u64, CacheKey>,
+    tick: u64,
+}
+
+impl Inner {
+    fn touch(&mut self, key: &CacheKey) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.recency.insert(tick, key.clone());
+        }
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+}
+
+impl MemoryCache {
+    /// Creates a cache holding up to `max_entries` results, caching found results for an hour
+    /// and not-found results for five minutes.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                max_entries: max_entries.max(1),
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            })),
+            ttl: Duration::from_secs(60 * 60),
+            not_found_ttl: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Sets how long found results are kept.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long "no licenses found" results are kept.
+    pub fn with_not_found_ttl(mut self, ttl: Duration) -> Self {
+        self.not_found_ttl = ttl;
+        self
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The number of cached results, including any that have expired but not yet been removed.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Checks if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Option<CachedResult> {
+        let mut inner = self.lock();
+        let expired = match inner.entries.get(key) {
+            None => return None,
+            Some(entry) => entry.expires <= Instant::now(),
+        };
+
+        if expired {
+            inner.remove(key);
+            return None;
+        }
+
+        inner.touch(key);
+        inner.entries.get(key).map(|entry| entry.result.clone())
+    }
+
+    /// Evicts the least recently used entry if the cache is full.
+    fn insert(&self, key: CacheKey, result: CachedResult) {
+        let ttl = match result {
+            CachedResult::Found(_) => self.ttl,
+            CachedResult::NotFound => self.not_found_ttl,
+        };
+        if ttl.is_zero() {
+            return;
+        }
+
+        let mut inner = self.lock();
+        inner.remove(&key);
+        while inner.entries.len() >= inner.max_entries {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+
+        inner.entries.insert(
+            key.clone(),
+            Entry {
+                result,
+                expires: Instant::now() + ttl,
+                last_used: 0,
+            },
+        );
+        inner.touch(&key);
+    }
+
+    fn invalidate_license(&self, license_no: &str) {
+        let direct = CacheKey::license(license_no);
+        let mut inner = self.lock();
+        let keys: Vec<CacheKey> = inner
+            .entries
+            .iter()
+            .filter(|(key, entry)| **key == direct || entry.result.contains_license(license_no))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in keys {
+            inner.remove(&key);
+        }
+    }
+
+    fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let cache = MemoryCache::new(2);
+
+        cache.insert(CacheKey::license("1"), CachedResult::NotFound);
+        cache.insert(CacheKey::license("2"), CachedResult::NotFound);
+        assert!(cache.get(&CacheKey::license("1")).is_some());
+
+        cache.insert(CacheKey::license("3"), CachedResult::NotFound);
+
+        assert!(cache.get(&CacheKey::license("1")).is_some());
+        assert!(cache.get(&CacheKey::license("2")).is_none());
+        assert!(cache.get(&CacheKey::license("3")).is_some());
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = MemoryCache::new(10)
+            .with_ttl(Duration::from_secs(60))
+            .with_not_found_ttl(Duration::from_millis(1));
+
+        cache.insert(CacheKey::license("1"), CachedResult::NotFound);
+        cache.insert(CacheKey::license("2"), CachedResult::Found(Vec::new()));
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert!(cache.get(&CacheKey::license("1")).is_none());
+        assert!(cache.get(&CacheKey::license("2")).is_some());
+    }
+
+    #[test]
+    fn test_invalidate_license() {
+        let cache = MemoryCache::new(10);
+
+        cache.insert(CacheKey::license("1234 5678"), CachedResult::NotFound);
+        cache.insert(CacheKey::license("9999"), CachedResult::NotFound);
+        cache.invalidate_license("12345678");
+
+        assert!(cache.get(&CacheKey::license("12345678")).is_none());
+        assert_eq!(cache.len(), 1);
+    }
+}
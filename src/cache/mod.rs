@@ -0,0 +1,145 @@
+use std::fmt::Debug;
+
+pub use file::FileCache;
+pub use memory::MemoryCache;
+
+mod file;
+mod memory;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::payloads::{SearchByLicense, SearchByName};
+use crate::models::LicenseState;
+use crate::SIAError;
+
+/// Identifies a search, normalised so trivially different spellings of the same query share a cache entry.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CacheKey {
+    /// A search by license number, holding only its alphanumeric characters in upper case.
+    License(String),
+    /// A search by name, holding the trimmed, lower-cased form fields in payload order.
+    Name(Vec<String>),
+}
+
+pub(crate) fn normalise_license(license_no: &str) -> String {
+    license_no
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn normalise_field(field: &str) -> String {
+    field
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl CacheKey {
+    /// Builds the key for a license number search.
+    pub fn license(license_no: &str) -> Self {
+        CacheKey::License(normalise_license(license_no))
+    }
+}
+
+impl From<&SearchByLicense> for CacheKey {
+    fn from(payload: &SearchByLicense) -> Self {
+        CacheKey::license(&payload.license_no)
+    }
+}
+
+impl From<&SearchByName> for CacheKey {
+    fn from(payload: &SearchByName) -> Self {
+        CacheKey::Name(
+            payload
+                .to_params()
+                .into_iter()
+                .map(|(_, value)| normalise_field(value))
+                .collect(),
+        )
+    }
+}
+
+/// Storage for search results, consulted by a client before it contacts the register.
+///
+/// Implementations decide how long results stay valid. Methods are synchronous and are called
+/// from async code, so implementations should avoid long blocking operations.
+pub trait CacheBackend: Debug + Send + Sync {
+    /// Returns the cached result for a key, if there is one that hasn't expired.
+    fn get(&self, key: &CacheKey) -> Option<CachedResult>;
+
+    /// Stores a result.
+    fn insert(&self, key: CacheKey, result: CachedResult);
+
+    /// Removes every cached result for the given license number, including name searches that returned it.
+    fn invalidate_license(&self, license_no: &str);
+
+    /// Removes every cached result.
+    fn clear(&self);
+}
+
+/// The outcome of a search that is worth remembering.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CachedResult {
+    /// The search returned these licenses.
+    Found(Vec<LicenseState>),
+    /// The register reported no matching licenses.
+    NotFound,
+}
+
+impl CachedResult {
+    /// Converts a search result into a cacheable one. Failures other than "no licenses found" are not cached.
+    pub fn from_result(result: &Result<Vec<LicenseState>, SIAError>) -> Option<Self> {
+        match result {
+            Ok(licenses) => Some(CachedResult::Found(licenses.clone())),
+            Err(SIAError::NoLicensesFound) => Some(CachedResult::NotFound),
+            Err(_) => None,
+        }
+    }
+
+    /// Converts back into the result the search originally returned.
+    pub fn into_result(self) -> Result<Vec<LicenseState>, SIAError> {
+        match self {
+            CachedResult::Found(licenses) => Ok(licenses),
+            CachedResult::NotFound => Err(SIAError::NoLicensesFound),
+        }
+    }
+
+    /// Checks if this result contains the given license number.
+    pub(crate) fn contains_license(&self, license_no: &str) -> bool {
+        let license_no = normalise_license(license_no);
+        match self {
+            CachedResult::Found(licenses) => licenses
+                .iter()
+                .any(|license| normalise_license(license.license_number_str()) == license_no),
+            CachedResult::NotFound => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_normalised() {
+        assert_eq!(
+            CacheKey::license("1234 5678-9012 3456"),
+            CacheKey::license("1234567890123456")
+        );
+
+        let a = SearchByName {
+            last_name: " Smith ".to_string(),
+            first_name: "JOHN".to_string(),
+            ..Default::default()
+        };
+        let b = SearchByName {
+            last_name: "smith".to_string(),
+            first_name: "John".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(CacheKey::from(&a), CacheKey::from(&b));
+    }
+}
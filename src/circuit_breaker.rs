@@ -0,0 +1,208 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The state of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent as normal.
+    Closed,
+    /// The register looks unavailable, so requests fail immediately.
+    Open,
+    /// The cooldown has passed and a single trial request is allowed through to test the register.
+    HalfOpen,
+}
+
+/// Stops sending requests to the register after repeated failures, so callers fail fast instead of
+/// waiting out every retry while it is down.
+///
+/// The breaker opens after `failure_threshold` consecutive failures, where a failure is a request that
+/// got no response or a 5xx response. While open, searches return [`crate::SIAError::ServiceUnavailable`]
+/// without contacting the register. Once `cooldown` has passed the breaker half-opens and lets one trial
+/// request through: if it succeeds the breaker closes, otherwise it opens again for another cooldown.
+///
+/// Cloning a breaker shares its state, so one breaker can guard many clients.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use sia_rs::{CircuitBreaker, CircuitState, SiaClient};
+///
+/// let breaker = CircuitBreaker::new(5, Duration::from_secs(30));
+/// let client = SiaClient::builder().circuit_breaker(breaker.clone()).build().unwrap();
+///
+/// assert_eq!(breaker.state(), CircuitState::Closed);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    failure_threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    opened_at: Option<Instant>,
+    trial_started: Option<Instant>,
+}
+
+impl Inner {
+    fn state(&self, now: Instant) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if now.saturating_duration_since(opened_at) < self.cooldown => {
+                CircuitState::Open
+            }
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Creates a breaker that opens after `failure_threshold` consecutive failures
+    /// and half-opens after `cooldown`.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                failure_threshold: failure_threshold.max(1),
+                cooldown,
+                failures: 0,
+                opened_at: None,
+                trial_started: None,
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The current state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        self.lock().state(Instant::now())
+    }
+
+    /// The number of consecutive failures seen since the last success.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.lock().failures
+    }
+
+    /// Returns how long until the breaker half-opens, or `None` if it isn't open.
+    pub fn time_until_half_open(&self) -> Option<Duration> {
+        let inner = self.lock();
+        let opened_at = inner.opened_at?;
+        let remaining = inner.cooldown.saturating_sub(opened_at.elapsed());
+        (!remaining.is_zero()).then_some(remaining)
+    }
+
+    /// Closes the breaker and forgets any failures.
+    pub fn reset(&self) {
+        let mut inner = self.lock();
+        inner.failures = 0;
+        inner.opened_at = None;
+        inner.trial_started = None;
+    }
+
+    /// Checks if a request may be sent now.
+    ///
+    /// While half-open only one trial request is allowed at a time. If the trial never reports back,
+    /// another is allowed after a further cooldown.
+    pub(crate) fn try_acquire(&self) -> bool {
+        let now = Instant::now();
+        let mut inner = self.lock();
+
+        match inner.state(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                let trial_running = inner
+                    .trial_started
+                    .is_some_and(|started| now.saturating_duration_since(started) < inner.cooldown);
+                if trial_running {
+                    return false;
+                }
+                inner.trial_started = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Records a request that reached the register.
+    pub(crate) fn record_success(&self) {
+        self.reset();
+    }
+
+    /// Records a request that failed, opening the breaker if the threshold has been reached
+    /// or a half-open trial failed.
+    pub(crate) fn record_failure(&self) {
+        let mut inner = self.lock();
+        inner.failures = inner.failures.saturating_add(1);
+
+        if inner.opened_at.is_some() || inner.failures >= inner.failure_threshold {
+            if inner.opened_at.is_none() {
+                log::warn!(
+                    "Circuit breaker opened after {} consecutive failures",
+                    inner.failures
+                );
+            }
+            inner.opened_at = Some(Instant::now());
+            inner.trial_started = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.try_acquire());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+        assert!(breaker.time_until_half_open().is_some());
+
+        breaker.reset();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_success_resets_the_count() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_allows_one_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+
+        breaker.record_failure();
+        assert!(!breaker.try_acquire());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire());
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,913 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream, StreamExt};
+
+use crate::cache::CacheBackend;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::coverage::{Activity, Coverage};
+use crate::errors::SIAError;
+use crate::models::payloads::{SearchByLicense, SearchByName};
+use crate::models::{LicenseNumber, LicenseState, Query, SearchOutcome, ToQuery};
+use crate::rate_limit::RateLimiter;
+use crate::requests::core::{ClientCore, SearchRequest};
+use crate::requests::requests_async::request_base;
+use crate::retry::RetryPolicy;
+use crate::single_flight::{SearchResult, SingleFlight};
+use crate::split::{self, SplitSearch};
+use crate::transport::{ReqwestTransport, Transport};
+use crate::verify::{Candidate, Verification};
+use crate::DEFAULT_BASE_URL;
+
+/// Builder for [`SiaClient`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use sia_rs::SiaClient;
+///
+/// let client = SiaClient::builder()
+///     .base_url("http://localhost:8080")
+///     .timeout(Duration::from_secs(10))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SiaClientBuilder {
+    pub(crate) base_url: String,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) rate_limiter: RateLimiter,
+    pub(crate) circuit_breaker: Option<CircuitBreaker>,
+    pub(crate) strict_parsing: bool,
+    pub(crate) split_broad_searches: bool,
+    pub(crate) cache: Option<Arc<dyn CacheBackend>>,
+    pub(crate) deduplicate: bool,
+    pub(crate) transport: Option<Arc<dyn Transport>>,
+    #[cfg(feature = "blocking")]
+    pub(crate) blocking_transport: Option<Arc<dyn crate::blocking::Transport>>,
+}
+
+impl Default for SiaClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            timeout: None,
+            user_agent: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: RateLimiter::shared(),
+            circuit_breaker: None,
+            strict_parsing: false,
+            split_broad_searches: false,
+            cache: None,
+            deduplicate: true,
+            transport: None,
+            #[cfg(feature = "blocking")]
+            blocking_transport: None,
+        }
+    }
+}
+
+impl SiaClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base URL of the register, e.g. `https://services.sia.homeoffice.gov.uk`.
+    /// Any trailing slash is ignored.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sets the timeout applied to each individual HTTP request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with each request.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sets the policy used to retry failed requests.
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the rate limiter every request from this client goes through.
+    /// Defaults to [`RateLimiter::shared`].
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Sets a circuit breaker that stops requests to the register after repeated failures.
+    /// There is no circuit breaker by default.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Sets whether a license with any missing, defaulted or unrecognised field fails with
+    /// [`SIAError::ParseFailed`] instead of being returned with guessed values. Off by default,
+    /// in which case each license's [`crate::ParseReport`] lists any gaps.
+    pub fn strict_parsing(mut self, strict_parsing: bool) -> Self {
+        self.strict_parsing = strict_parsing;
+        self
+    }
+
+    /// Sets whether a name search that matches too many licenses is automatically split into narrower
+    /// searches, one for each role and sector the query leaves open, with the results combined.
+    /// If any narrowed search is still too broad the search fails with [`SIAError::TooManyResults`];
+    /// use [`SiaClient::search_split`] to get the partial results instead. Off by default.
+    pub fn split_broad_searches(mut self, split_broad_searches: bool) -> Self {
+        self.split_broad_searches = split_broad_searches;
+        self
+    }
+
+    /// Enables caching of search results, e.g. with a [`crate::MemoryCache`] or [`crate::FileCache`].
+    /// Caching is off by default.
+    pub fn cache(mut self, cache: impl CacheBackend + 'static) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Sets whether identical searches made at the same time share a single request. On by default.
+    pub fn deduplicate(mut self, deduplicate: bool) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+
+    /// Sets the transport the async client sends requests through, e.g. a [`crate::FixtureTransport`] in tests.
+    /// When set, `timeout` and `user_agent` are left to the transport.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sets the transport the blocking client sends requests through.
+    /// When set, `timeout` and `user_agent` are left to the transport.
+    #[cfg(feature = "blocking")]
+    pub fn blocking_transport(
+        mut self,
+        transport: impl crate::blocking::Transport + 'static,
+    ) -> Self {
+        self.blocking_transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Builds the async client.
+    ///
+    /// Fails with [`SIAError::ClientBuild`] if the HTTP client can't be built, e.g. because the user agent
+    /// isn't a valid header value.
+    pub fn build(self) -> Result<SiaClient, SIAError> {
+        let transport: Arc<dyn Transport> = match self.transport.clone() {
+            Some(transport) => transport,
+            None => {
+                let mut http = reqwest::Client::builder();
+                if let Some(timeout) = self.timeout {
+                    http = http.timeout(timeout);
+                }
+                if let Some(user_agent) = &self.user_agent {
+                    http = http.user_agent(user_agent);
+                }
+                let http = http
+                    .build()
+                    .map_err(|err| SIAError::ClientBuild { source: err.into() })?;
+                Arc::new(ReqwestTransport::new(http))
+            }
+        };
+
+        Ok(SiaClient {
+            core: ClientCore::from_builder(&self),
+            transport,
+            in_flight: self.deduplicate.then(|| Arc::new(SingleFlight::new())),
+        })
+    }
+
+    /// Builds the blocking client.
+    ///
+    /// Fails with [`SIAError::ClientBuild`] if the HTTP client can't be built.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<crate::blocking::SiaClient, SIAError> {
+        crate::blocking::SiaClient::from_builder(self)
+    }
+}
+
+/// A reusable client for the SIA public register.
+///
+/// Holds a single transport, by default a pooled HTTP client, so connections are reused between searches.
+/// Cloning the client is cheap and shares the underlying connection pool.
+#[derive(Debug, Clone)]
+pub struct SiaClient {
+    core: ClientCore,
+    transport: Arc<dyn Transport>,
+    in_flight: Option<Arc<SingleFlight<SearchResult>>>,
+}
+
+impl Default for SiaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SiaClient {
+    /// Creates a client pointed at the public SIA register.
+    pub fn new() -> Self {
+        SiaClientBuilder::default()
+            .build()
+            .expect("the default client configuration is valid")
+    }
+
+    /// Creates a builder for configuring a client.
+    pub fn builder() -> SiaClientBuilder {
+        SiaClientBuilder::default()
+    }
+
+    /// The base URL this client sends requests to.
+    pub fn base_url(&self) -> &str {
+        &self.core.base_url
+    }
+
+    /// The policy used to retry failed requests.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.core.retry
+    }
+
+    /// The rate limiter every request from this client goes through.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.core.rate_limiter
+    }
+
+    /// The circuit breaker guarding requests from this client, if one is set.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.core.circuit_breaker.as_ref()
+    }
+
+    /// The cache search results are stored in, if caching is enabled.
+    pub fn cache(&self) -> Option<&dyn CacheBackend> {
+        self.core.cache.as_deref()
+    }
+
+    /// Search for a license by either license number or name.
+    ///
+    /// The query is checked with [`Query::validate`] first, so an invalid query fails without contacting the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A query object that contains the search parameters.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LicenseState>, SIAError>` - A vector of license states if the search was successful, otherwise an error.
+    pub async fn search(&self, query: &Query) -> Result<Vec<LicenseState>, SIAError> {
+        match self.search_once(query).await {
+            Err(SIAError::TooManyResults) if self.core.split_broad_searches => {
+                self.split(query).await?.into_result()
+            }
+            result => result,
+        }
+    }
+
+    /// Searches like [`SiaClient::search`], but reports "no licenses found" and "too many results" as a
+    /// [`SearchOutcome`] rather than an error, so `Err` only means the search couldn't be completed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sia_rs::{Query, SearchOutcome, SiaClient};
+    ///
+    /// # async fn run() -> Result<(), sia_rs::SIAError> {
+    /// let query = Query::new().with_license_no("1234567890123456".to_string());
+    ///
+    /// match SiaClient::new().lookup(&query).await? {
+    ///     SearchOutcome::Found(licenses) => println!("{} licenses", licenses.len()),
+    ///     SearchOutcome::NotFound => println!("Not on the register"),
+    ///     SearchOutcome::TooMany => println!("Too many matches, narrow the search"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A query object that contains the search parameters.
+    pub async fn lookup(&self, query: &Query) -> Result<SearchOutcome, SIAError> {
+        SearchOutcome::from_result(self.search(query).await)
+    }
+
+    /// Searches, and if the query matches too many licenses, splits it into narrower searches by role
+    /// and sector and combines their results.
+    ///
+    /// Unlike [`SiaClient::search`] with [`SiaClientBuilder::split_broad_searches`], this returns whatever
+    /// was found even when some narrowed searches were still too broad, and lists those searches.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A query object that contains the search parameters.
+    pub async fn search_split(&self, query: &Query) -> Result<SplitSearch, SIAError> {
+        match self.search_once(query).await {
+            Err(SIAError::TooManyResults) => self.split(query).await,
+            result => Ok(SplitSearch {
+                licenses: result?,
+                too_broad: Vec::new(),
+            }),
+        }
+    }
+
+    /// Searches without splitting broad queries.
+    async fn search_once(&self, query: &Query) -> Result<Vec<LicenseState>, SIAError> {
+        self.execute(SearchRequest::from_query(query)?).await
+    }
+
+    /// Runs a narrowed search for every role and sector the query leaves open.
+    async fn split(&self, query: &Query) -> Result<SplitSearch, SIAError> {
+        let narrowed = split::sub_queries(query);
+        if narrowed.is_empty() {
+            return split::merge([(query.clone(), Err(SIAError::TooManyResults))]);
+        }
+
+        let results: Vec<_> = stream::iter(narrowed)
+            .map(|query| async move {
+                let result = self.search_once(&query).await;
+                (query, result)
+            })
+            .buffer_unordered(split::SPLIT_CONCURRENCY)
+            .collect()
+            .await;
+
+        split::merge(results)
+    }
+
+    /// Runs many searches with at most `concurrency` in flight at once.
+    ///
+    /// Results are yielded in the order they complete, each paired with the input that produced it.
+    /// Every search goes through this client's rate limiter, retry policy and cache.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use sia_rs::SiaClient;
+    ///
+    /// # async fn run() {
+    /// let client = SiaClient::new();
+    /// let mut results = client.search_many(vec!["1234567890123456", "6543210987654321"], 4);
+    ///
+    /// while let Some((license_no, result)) = results.next().await {
+    ///     println!("{}: {:?}", license_no, result);
+    /// }
+    /// # }
+    /// ```
+    pub fn search_many<'a, I>(
+        &'a self,
+        inputs: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = (I::Item, Result<Vec<LicenseState>, SIAError>)> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+        I::Item: ToQuery + 'a,
+    {
+        stream::iter(inputs)
+            .map(move |input| async move {
+                let result = self.search(&input.to_query()).await;
+                (input, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    /// Looks up a candidate's license number and checks the license against the details they gave.
+    ///
+    /// A license number the register doesn't know gives [`crate::Verdict::NotFound`] rather than an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The license number and details to check.
+    pub async fn verify(&self, candidate: &Candidate) -> Result<Verification, SIAError> {
+        let query = Query::new().with_license(candidate.license_number.clone());
+
+        match self.search(&query).await {
+            Ok(licenses) => Ok(candidate.check(&licenses)),
+            Err(SIAError::NoLicensesFound) => Ok(candidate.check(&[])),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Looks up a license number and checks whether the license lets its holder carry out an activity today.
+    ///
+    /// A license number the register doesn't know gives [`crate::CoverageProblem::NotFound`] rather than an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `license_no` - The license to check.
+    /// * `activity` - The work the holder needs to be licensed for.
+    pub async fn check_coverage(
+        &self,
+        license_no: &LicenseNumber,
+        activity: &Activity,
+    ) -> Result<Coverage, SIAError> {
+        let query = Query::new().with_license(license_no.clone());
+        let outcome = self.lookup(&query).await?;
+
+        Ok(Coverage::check(outcome.licenses(), activity))
+    }
+
+    /// Search for a license by license number.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - A SearchByLicense object that contains the search parameters.
+    pub async fn search_by_license(
+        &self,
+        payload: SearchByLicense,
+    ) -> Result<Vec<LicenseState>, SIAError> {
+        self.execute(SearchRequest::by_license(&payload)).await
+    }
+
+    /// Search for a license by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - A SearchByName object that contains the search parameters.
+    pub async fn search_by_name(
+        &self,
+        payload: SearchByName,
+    ) -> Result<Vec<LicenseState>, SIAError> {
+        self.execute(SearchRequest::by_name(&payload)).await
+    }
+
+    /// Sends a search, consulting the cache first if one is configured
+    /// and sharing the request with any identical search already in flight.
+    async fn execute(&self, request: SearchRequest) -> Result<Vec<LicenseState>, SIAError> {
+        if let Some(cached) = self.core.cached(&request) {
+            return cached;
+        }
+
+        match &self.in_flight {
+            Some(in_flight) => in_flight.run(&request.key, || self.fetch(&request)).await,
+            None => self.fetch(&request).await,
+        }
+    }
+
+    /// Sends a search and stores the result in the cache.
+    async fn fetch(&self, request: &SearchRequest) -> Result<Vec<LicenseState>, SIAError> {
+        let result = request_base(
+            self.transport.as_ref(),
+            &self.core.url(request),
+            &request.form(),
+            &self.core.retry,
+            &self.core.rate_limiter,
+            self.core.circuit_breaker.as_ref(),
+            self.core.strict_parsing,
+        )
+        .await;
+        self.core.store(request, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::models::{LicenseRole, LicenseSector};
+    use crate::transport::{
+        FixtureTransport, TransportError, TransportErrorKind, TransportResponse,
+    };
+    use crate::SEARCH_LICENSE_NUM_PATH;
+
+    /// Serves `responses` in order on a local port, one per connection, and returns the base URL.
+    pub(crate) fn serve(responses: Vec<(u16, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_builder_trims_base_url() {
+        let client = SiaClient::builder()
+            .base_url("http://localhost:8080/")
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base_url(), "http://localhost:8080");
+        assert_eq!(SiaClient::new().base_url(), DEFAULT_BASE_URL);
+    }
+
+    #[test]
+    fn test_invalid_http_settings_fail_to_build() {
+        let result = SiaClient::builder().user_agent("bad\nagent").build();
+
+        assert!(matches!(result, Err(SIAError::ClientBuild { .. })));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_search_against_local_server() {
+        let base_url = serve(vec![(200, "<p>No results found</p>".to_string())]);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        let result = client
+            .search(&Query::new().with_license_no("1234567890123456".to_string()))
+            .await;
+
+        assert!(matches!(result, Err(SIAError::NoLicensesFound)));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_repeated_search_is_cached() {
+        let base_url = serve(vec![(200, "<p>No results found</p>".to_string())]);
+        let cache = crate::MemoryCache::new(10);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .retry_policy(RetryPolicy::none())
+            .cache(cache.clone())
+            .build()
+            .unwrap();
+        let query = Query::new().with_license_no("1234567890123456".to_string());
+
+        assert!(matches!(
+            client.search(&query).await,
+            Err(SIAError::NoLicensesFound)
+        ));
+        assert!(matches!(
+            client.search(&query).await,
+            Err(SIAError::NoLicensesFound)
+        ));
+
+        cache.invalidate_license("1234 5678 9012 3456");
+        assert!(matches!(
+            client.search(&query).await,
+            Err(SIAError::RequestFailed { .. })
+        ));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_concurrent_searches_share_a_request() {
+        let base_url = serve(vec![(200, "<p>No results found</p>".to_string())]);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+        let query = Query::new().with_license_no("1234567890123456".to_string());
+
+        let (a, b, c) = tokio::join!(
+            client.search(&query),
+            client.search(&query),
+            client.search(&query)
+        );
+
+        assert!(matches!(a, Err(SIAError::NoLicensesFound)));
+        assert!(matches!(b, Err(SIAError::NoLicensesFound)));
+        assert!(matches!(c, Err(SIAError::NoLicensesFound)));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_search_many() {
+        let base_url = serve(vec![
+            (200, "<p>No results found</p>".to_string()),
+            (200, "<p>No results found</p>".to_string()),
+            (200, "<p>No results found</p>".to_string()),
+        ]);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+
+        let mut results: Vec<_> = client
+            .search_many(
+                vec!["1111111111111111", "2222222222222222", "3333333333333333"],
+                2,
+            )
+            .collect()
+            .await;
+        results.sort_by_key(|(input, _)| *input);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, "1111111111111111");
+        assert!(results
+            .iter()
+            .all(|(_, result)| matches!(result, Err(SIAError::NoLicensesFound))));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_search_with_fixture_transport() {
+        let transport = FixtureTransport::new()
+            .with_license(
+                "1234567890123456",
+                TransportResponse::ok(include_str!("../fixtures/license_found.html")),
+            )
+            .with_surname(
+                "Smith",
+                TransportResponse::ok(include_str!("../fixtures/too_many_results.html")),
+            );
+        let client = SiaClient::builder()
+            .transport(transport.clone())
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        let licenses = client
+            .search(&Query::new().with_license_no("1234567890123456".to_string()))
+            .await
+            .unwrap();
+        let result = client
+            .search(&Query::new().with_last_name("Smith".to_string()))
+            .await;
+
+        assert_eq!(licenses.len(), 1);
+        assert_eq!(licenses[0].first_name, "JOHN");
+        assert_eq!(licenses[0].last_name, "SMITH");
+        assert_eq!(licenses[0].license_number_str(), "1234567890123456");
+        assert_eq!(licenses[0].role, LicenseRole::Frontline);
+        assert_eq!(licenses[0].sector, LicenseSector::DoorSupervision);
+        assert_eq!(
+            licenses[0].expiry,
+            chrono::NaiveDate::from_ymd_opt(2030, 1, 1)
+        );
+        assert_eq!(licenses[0].status, crate::LicenseStatus::Active);
+        assert_eq!(licenses[0].status_reason, "This licence is valid.");
+        assert_eq!(licenses[0].license_conditions, "None");
+        assert!(matches!(result, Err(SIAError::TooManyResults)));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].url.ends_with(SEARCH_LICENSE_NUM_PATH));
+        assert_eq!(requests[1].param("Surname"), Some("Smith"));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_client_errors_fail_without_retrying() {
+        let base_url = serve(vec![(404, String::new())]);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let result = client
+            .search(&Query::new().with_license_no("1234567890123456".to_string()))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SIAError::UnexpectedStatus { status: 404, .. })
+        ));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_server_errors_are_retried() {
+        let base_url = serve(vec![
+            (503, String::new()),
+            (200, "<p>No results found</p>".to_string()),
+        ]);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .retry_policy(RetryPolicy::new().with_base_delay(Duration::from_millis(10)))
+            .build()
+            .unwrap();
+
+        let result = client
+            .search(&Query::new().with_license_no("1234567890123456".to_string()))
+            .await;
+
+        assert!(matches!(result, Err(SIAError::NoLicensesFound)));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_open_circuit_fails_fast() {
+        let transport = FixtureTransport::new();
+        for _ in 0..2 {
+            transport.push_error(TransportError::new(TransportErrorKind::Connect, "refused"));
+        }
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        let client = SiaClient::builder()
+            .transport(transport.clone())
+            .rate_limiter(RateLimiter::unlimited())
+            .retry_policy(RetryPolicy::new().with_base_delay(Duration::from_millis(1)))
+            .circuit_breaker(breaker.clone())
+            .build()
+            .unwrap();
+        let query = Query::new().with_license_no("1234567890123456".to_string());
+
+        assert!(matches!(
+            client.search(&query).await,
+            Err(SIAError::ServiceUnavailable)
+        ));
+        assert!(matches!(
+            client.search(&query).await,
+            Err(SIAError::ServiceUnavailable)
+        ));
+        assert_eq!(transport.requests().len(), 2);
+        assert_eq!(breaker.state(), crate::CircuitState::Open);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_license_numbers_are_checked_locally() {
+        let transport =
+            FixtureTransport::new().with_default(TransportResponse::ok("<p>No results found</p>"));
+        let client = SiaClient::builder()
+            .transport(transport.clone())
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        let result = client
+            .search(&Query::new().with_license_no("123456".to_string()))
+            .await;
+        assert!(matches!(result, Err(SIAError::InvalidQuery { .. })));
+        assert!(transport.requests().is_empty());
+
+        let result = client
+            .search(&Query::new().with_license_no("1234-5678-9012-3456".to_string()))
+            .await;
+        assert!(matches!(result, Err(SIAError::NoLicensesFound)));
+        assert_eq!(
+            transport.requests()[0].param("LicenseNo"),
+            Some("1234567890123456")
+        );
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_verify() {
+        let transport = FixtureTransport::new()
+            .with_license(
+                "1234567890123456",
+                TransportResponse::ok(include_str!("../fixtures/license_found.html")),
+            )
+            .with_default(TransportResponse::ok(include_str!(
+                "../fixtures/no_results.html"
+            )));
+        let client = SiaClient::builder()
+            .transport(transport)
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        let candidate = Candidate::new("1234 5678 9012 3456".parse().unwrap(), "John", "Smith")
+            .with_sector(LicenseSector::DoorSupervision);
+        let verification = client.verify(&candidate).await.unwrap();
+        assert_eq!(verification.verdict, crate::Verdict::Match);
+
+        let candidate = candidate.with_sector(LicenseSector::CloseProtection);
+        let verification = client.verify(&candidate).await.unwrap();
+        assert_eq!(verification.verdict, crate::Verdict::SectorMismatch);
+        assert_eq!(verification.reasons.len(), 1);
+
+        let candidate = Candidate::new("6543210987654321".parse().unwrap(), "John", "Smith");
+        let verification = client.verify(&candidate).await.unwrap();
+        assert_eq!(verification.verdict, crate::Verdict::NotFound);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_check_coverage() {
+        let transport = FixtureTransport::new()
+            .with_license(
+                "1234 5678 9012 3456",
+                TransportResponse::ok(include_str!("../fixtures/license_found.html")),
+            )
+            .with_default(TransportResponse::ok(include_str!(
+                "../fixtures/no_results.html"
+            )));
+        let client = SiaClient::builder()
+            .transport(transport)
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+        let license_no = "1234 5678 9012 3456".parse().unwrap();
+
+        let guarding = crate::Activity::frontline(LicenseSector::SecurityGuard);
+        let coverage = client.check_coverage(&license_no, &guarding).await.unwrap();
+        assert!(coverage.is_permitted());
+
+        let cctv = crate::Activity::frontline(LicenseSector::PublicSpaceSurveillance);
+        let coverage = client.check_coverage(&license_no, &cctv).await.unwrap();
+        assert!(matches!(
+            coverage.reasons[..],
+            [crate::CoverageProblem::Sector { .. }]
+        ));
+
+        let unknown = "6543210987654321".parse().unwrap();
+        let coverage = client.check_coverage(&unknown, &guarding).await.unwrap();
+        assert_eq!(coverage.reasons, vec![crate::CoverageProblem::NotFound]);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_lookup_separates_negative_answers() {
+        let transport = FixtureTransport::new()
+            .with_license(
+                "1234567890123456",
+                TransportResponse::ok(include_str!("../fixtures/license_found.html")),
+            )
+            .with_default(TransportResponse::ok(include_str!(
+                "../fixtures/no_results.html"
+            )));
+        transport.push_response(TransportResponse::ok(include_str!(
+            "../fixtures/too_many_results.html"
+        )));
+        let client = SiaClient::builder()
+            .transport(transport)
+            .rate_limiter(RateLimiter::unlimited())
+            .build()
+            .unwrap();
+
+        let outcome = client
+            .lookup(&Query::new().with_last_name("Smith".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(outcome, SearchOutcome::TooMany);
+
+        let outcome = client
+            .lookup(&Query::new().with_license_no("1234567890123456".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(outcome.licenses().len(), 1);
+
+        let outcome = client
+            .lookup(&Query::new().with_license_no("6543210987654321".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(outcome, SearchOutcome::NotFound);
+
+        assert!(matches!(
+            client.lookup(&Query::new()).await,
+            Err(SIAError::InvalidQuery { .. })
+        ));
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_broad_searches_are_split() {
+        let found = TransportResponse::ok(include_str!("../fixtures/license_found.html"));
+        let too_many = TransportResponse::ok(include_str!("../fixtures/too_many_results.html"));
+        let transport = FixtureTransport::new().with_default(TransportResponse::ok(include_str!(
+            "../fixtures/no_results.html"
+        )));
+        transport.push_response(too_many.clone());
+        transport.push_response(found.clone());
+        transport.push_response(found);
+        let client = SiaClient::builder()
+            .transport(transport.clone())
+            .rate_limiter(RateLimiter::unlimited())
+            .split_broad_searches(true)
+            .build()
+            .unwrap();
+        let query = Query::new().with_last_name("Smith".to_string());
+
+        let licenses = client.search(&query).await.unwrap();
+        assert_eq!(licenses.len(), 1);
+        assert_eq!(transport.requests().len(), 15);
+
+        let transport = FixtureTransport::new().with_default(too_many);
+        let client = SiaClient::builder()
+            .transport(transport)
+            .rate_limiter(RateLimiter::unlimited())
+            .split_broad_searches(true)
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            client.search(&query).await,
+            Err(SIAError::TooManyResults)
+        ));
+        let split = client.search_split(&query).await.unwrap();
+        assert!(split.licenses.is_empty());
+        assert_eq!(split.too_broad.len(), 14);
+        assert!(!split.is_complete());
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::Display;
+
+use chrono::NaiveDate;
+
+use crate::models::{LicenseRole, LicenseSector, LicenseState, LicenseStatus};
+
+/// A piece of licensable work: the sector it falls under and whether it is front line.
+///
+/// # Example
+///
+/// ```
+/// use sia_rs::{Activity, LicenseSector};
+///
+/// let guarding = Activity::frontline(LicenseSector::SecurityGuard);
+///
+/// assert!(LicenseSector::DoorSupervision.covers(&guarding.sector));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    pub sector: LicenseSector,
+    pub role: LicenseRole,
+}
+
+impl Activity {
+    pub fn new(sector: LicenseSector, role: LicenseRole) -> Self {
+        Self { sector, role }
+    }
+
+    /// Front line work in a sector, e.g. working on a door.
+    pub fn frontline(sector: LicenseSector) -> Self {
+        Self::new(sector, LicenseRole::Frontline)
+    }
+
+    /// Non-front line work in a sector, e.g. managing or supervising those who work on a door.
+    pub fn non_frontline(sector: LicenseSector) -> Self {
+        Self::new(sector, LicenseRole::NonFrontline)
+    }
+}
+
+/// A reason a license doesn't permit an activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverageProblem {
+    /// The register has no license with that number.
+    NotFound,
+    /// The license's status doesn't allow its holder to work, e.g. it is suspended or revoked.
+    NotActive(LicenseStatus),
+    /// The license passed its expiry date.
+    Expired(NaiveDate),
+    /// The register didn't show an expiry date that could be read, so the license can't be shown to be in date.
+    UnknownExpiry,
+    /// The license's sector doesn't cover the activity's sector.
+    Sector {
+        required: LicenseSector,
+        held: LicenseSector,
+    },
+    /// The license's role doesn't cover the activity's role.
+    Role {
+        required: LicenseRole,
+        held: LicenseRole,
+    },
+}
+
+impl Display for CoverageProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoverageProblem::NotFound => write!(f, "license is not on the register"),
+            CoverageProblem::NotActive(status) => write!(f, "license is {}", status),
+            CoverageProblem::Expired(expiry) => write!(f, "license expired on {}", expiry),
+            CoverageProblem::UnknownExpiry => write!(f, "license expiry date is unknown"),
+            CoverageProblem::Sector { required, held } => {
+                write!(f, "a {} license doesn't cover {}", held, required)
+            }
+            CoverageProblem::Role { required, held } => {
+                write!(f, "a {} license doesn't cover {} work", held, required)
+            }
+        }
+    }
+}
+
+/// The result of checking whether a license permits an [`Activity`].
+///
+/// # Example
+///
+/// ```no_run
+/// use sia_rs::{Activity, LicenseSector, SiaClient};
+///
+/// # async fn run() -> Result<(), sia_rs::SIAError> {
+/// let license_no = "1234 5678 9012 3456".parse().unwrap();
+/// let activity = Activity::frontline(LicenseSector::SecurityGuard);
+///
+/// let coverage = SiaClient::new().check_coverage(&license_no, &activity).await?;
+/// if !coverage.is_permitted() {
+///     println!("Can't be rostered: {:?}", coverage.reasons);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub activity: Activity,
+    /// Every reason the license doesn't permit the activity. Empty if it does.
+    pub reasons: Vec<CoverageProblem>,
+    /// The license that was checked, if one was found.
+    pub license: Option<LicenseState>,
+}
+
+impl Coverage {
+    /// Checks the licenses returned for a license number against an activity, picking the license
+    /// with the fewest problems.
+    pub fn check(licenses: &[LicenseState], activity: &Activity) -> Self {
+        let best = licenses
+            .iter()
+            .map(|license| (license, problems(license, activity)))
+            .min_by_key(|(_, problems)| problems.len());
+
+        match best {
+            Some((license, reasons)) => Self {
+                activity: activity.clone(),
+                reasons,
+                license: Some(license.clone()),
+            },
+            None => Self {
+                activity: activity.clone(),
+                reasons: vec![CoverageProblem::NotFound],
+                license: None,
+            },
+        }
+    }
+
+    /// Checks if the license permits the activity.
+    pub fn is_permitted(&self) -> bool {
+        self.reasons.is_empty()
+    }
+}
+
+/// Lists every reason a license doesn't permit an activity today.
+pub(crate) fn problems(license: &LicenseState, activity: &Activity) -> Vec<CoverageProblem> {
+    let mut problems = Vec::new();
+
+    if !license.status.is_valid_to_work() {
+        problems.push(CoverageProblem::NotActive(license.status.clone()));
+    }
+
+    match license.expiry {
+        Some(expiry) if expiry < chrono::Local::now().date_naive() => {
+            problems.push(CoverageProblem::Expired(expiry));
+        }
+        Some(_) => {}
+        None => problems.push(CoverageProblem::UnknownExpiry),
+    }
+
+    if !license.sector.covers(&activity.sector) {
+        problems.push(CoverageProblem::Sector {
+            required: activity.sector.clone(),
+            held: license.sector.clone(),
+        });
+    }
+
+    if !license.role.covers(&activity.role) {
+        problems.push(CoverageProblem::Role {
+            required: activity.role.clone(),
+            held: license.role.clone(),
+        });
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(sector: LicenseSector, role: LicenseRole, status: LicenseStatus) -> LicenseState {
+        LicenseState {
+            role,
+            sector,
+            status,
+            ..LicenseState::example()
+        }
+    }
+
+    #[test]
+    fn test_coverage() {
+        let door = license(
+            LicenseSector::DoorSupervision,
+            LicenseRole::Frontline,
+            LicenseStatus::Active,
+        );
+        assert!(door.permits(&Activity::frontline(LicenseSector::SecurityGuard)));
+        assert!(door.permits(&Activity::non_frontline(LicenseSector::KeyHolding)));
+        assert!(!door.permits(&Activity::frontline(LicenseSector::PublicSpaceSurveillance)));
+
+        let manager = license(
+            LicenseSector::DoorSupervision,
+            LicenseRole::NonFrontline,
+            LicenseStatus::Active,
+        );
+        let coverage = Coverage::check(
+            &[manager],
+            &Activity::frontline(LicenseSector::DoorSupervision),
+        );
+        assert!(matches!(
+            coverage.reasons[..],
+            [CoverageProblem::Role { .. }]
+        ));
+
+        let mut lapsed = license(
+            LicenseSector::CloseProtection,
+            LicenseRole::Frontline,
+            LicenseStatus::Suspended,
+        );
+        lapsed.expiry = NaiveDate::from_ymd_opt(2000, 1, 1);
+        let coverage = Coverage::check(
+            &[lapsed],
+            &Activity::frontline(LicenseSector::DoorSupervision),
+        );
+        assert_eq!(
+            coverage.reasons,
+            vec![
+                CoverageProblem::NotActive(LicenseStatus::Suspended),
+                CoverageProblem::Expired(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
+            ]
+        );
+
+        let undated = LicenseState {
+            expiry: None,
+            ..LicenseState::example()
+        };
+        assert!(!undated.permits(&Activity::frontline(LicenseSector::DoorSupervision)));
+        assert_eq!(
+            Coverage::check(
+                &[undated],
+                &Activity::frontline(LicenseSector::DoorSupervision)
+            )
+            .reasons,
+            vec![CoverageProblem::UnknownExpiry]
+        );
+
+        let coverage = Coverage::check(&[], &Activity::frontline(LicenseSector::KeyHolding));
+        assert_eq!(coverage.reasons, vec![CoverageProblem::NotFound]);
+        assert!(!coverage.is_permitted());
+    }
+}
@@ -11,20 +11,139 @@
 //     NoLicenseContainersFound,
 // }
 
+use std::time::Duration;
+
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+use crate::models::{LicenseField, QueryProblem};
+use crate::transport::{TransportError, TransportErrorKind};
+
+/// Everything that can go wrong when searching the register.
+///
+/// New variants may be added, so matches need a wildcard arm. [`SIAError::is_not_found`] and
+/// [`SIAError::is_retryable`] cover the questions most callers ask.
+#[derive(Error, Debug, Clone)]
+#[non_exhaustive]
 pub enum SIAError {
-    #[error("Request failed: {0}")]
-    Error(String),
+    #[error("Invalid query: {}", .problems.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    InvalidQuery { problems: Vec<QueryProblem> },
 
     #[error("No licenses found.")]
     NoLicensesFound,
     #[error("Too many search results.")]
     TooManyResults,
-    #[error("Unable to parse license data.")]
-    ParseFailed,
+    #[error("Unable to parse license data{}: {snippet}", .field.map(|field| format!(" ({field})")).unwrap_or_default())]
+    ParseFailed {
+        /// The field that couldn't be parsed, or `None` if nothing on the page could be.
+        field: Option<LicenseField>,
+        /// The text that couldn't be understood, or an excerpt of the license if the field was missing.
+        snippet: String,
+    },
+    #[error("The register is down for maintenance.")]
+    Maintenance,
+    #[error("The request was blocked by the register's firewall.")]
+    Blocked,
+    #[error("The register asked for a CAPTCHA to be solved.")]
+    Captcha,
+    #[error("The register returned a page that couldn't be recognised: {snippet}")]
+    UnrecognisedPage { snippet: String },
+
+    #[error("Request to {url} failed after {attempts} attempt(s): {source}")]
+    RequestFailed {
+        url: String,
+        attempts: u32,
+        source: TransportError,
+    },
+    #[error("Request to {url} timed out after {attempts} attempt(s).")]
+    Timeout {
+        url: String,
+        attempts: u32,
+        source: TransportError,
+    },
+    #[error("Unable to read the response from {url}: {source}")]
+    BodyDecode { url: String, source: TransportError },
+    #[error("Request to {url} failed with status code {status} after {attempts} attempt(s).")]
+    UnexpectedStatus {
+        status: u16,
+        url: String,
+        attempts: u32,
+    },
+    #[error("Rate limited by the register, retry after {retry_after:?}.")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("The register is unavailable, not sending requests until it recovers.")]
+    ServiceUnavailable,
+    #[error("Unable to build the HTTP client: {source}")]
+    ClientBuild { source: TransportError },
+}
+
+impl SIAError {
+    /// Checks if the register was searched and has no matching license.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, SIAError::NoLicensesFound)
+    }
+
+    /// Checks if the same search might succeed if tried again later, e.g. after a timeout,
+    /// a server error or being rate limited.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SIAError::Timeout { .. }
+            | SIAError::RateLimited { .. }
+            | SIAError::ServiceUnavailable
+            | SIAError::Maintenance => true,
+            SIAError::RequestFailed { source, .. } => source.kind == TransportErrorKind::Connect,
+            SIAError::UnexpectedStatus { status, .. } => matches!(status, 408 | 429 | 500..=599),
+            _ => false,
+        }
+    }
+
+    /// Builds the error for a request that failed before a response was received.
+    pub(crate) fn from_transport(err: TransportError, url: &str, attempts: u32) -> Self {
+        let url = url.to_string();
+        match err.kind {
+            TransportErrorKind::Timeout => SIAError::Timeout {
+                url,
+                attempts,
+                source: err,
+            },
+            TransportErrorKind::Body => SIAError::BodyDecode { url, source: err },
+            TransportErrorKind::Connect | TransportErrorKind::Other => SIAError::RequestFailed {
+                url,
+                attempts,
+                source: err,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helpers() {
+        assert!(SIAError::NoLicensesFound.is_not_found());
+        assert!(!SIAError::NoLicensesFound.is_retryable());
+
+        let timeout = TransportError::new(TransportErrorKind::Timeout, "timed out");
+        let err = SIAError::from_transport(timeout, "http://localhost", 3);
+        assert!(matches!(err, SIAError::Timeout { attempts: 3, .. }));
+        assert!(err.is_retryable());
+        assert_eq!(
+            err.to_string(),
+            "Request to http://localhost timed out after 3 attempt(s)."
+        );
+
+        let body = TransportError::new(TransportErrorKind::Body, "truncated");
+        let err = SIAError::from_transport(body, "http://localhost", 1);
+        assert!(matches!(err, SIAError::BodyDecode { .. }));
+        assert!(!err.is_retryable());
 
-    #[error("Request failed: {0}")]
-    RequestFailed(reqwest::Error),
+        let status = |status| SIAError::UnexpectedStatus {
+            status,
+            url: "http://localhost".to_string(),
+            attempts: 1,
+        };
+        assert!(status(503).is_retryable());
+        assert!(!status(404).is_retryable());
+    }
 }
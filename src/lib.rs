@@ -1,13 +1,42 @@
+pub use crate::cache::{CacheBackend, CacheKey, CachedResult, FileCache, MemoryCache};
+pub use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+pub use crate::client::{SiaClient, SiaClientBuilder};
+pub use crate::coverage::{Activity, Coverage, CoverageProblem};
 pub use crate::errors::SIAError;
 pub use crate::models::payloads::{SearchByLicense, SearchByName};
-pub use crate::models::{LicenseRole, LicenseSector, LicenseState, Query};
+pub use crate::models::{
+    LicenseField, LicenseNumber, LicenseRole, LicenseSector, LicenseState, LicenseStatus,
+    ParseLicenseNumberError, ParseLicenseRoleError, ParseLicenseSectorError, ParseReport,
+    ParseStrategy, Query, QueryField, QueryProblem, SearchOutcome, ToQuery,
+};
+pub use crate::rate_limit::RateLimiter;
 #[cfg(feature = "blocking")]
 pub use crate::requests::blocking;
-
+pub use crate::retry::RetryPolicy;
+pub use crate::split::SplitSearch;
+pub use crate::transport::{
+    FixtureRequest, FixtureTransport, ReqwestTransport, Transport, TransportError,
+    TransportErrorKind, TransportResponse,
+};
+pub use crate::verify::{Candidate, Mismatch, Verdict, Verification};
+
+mod cache;
+mod circuit_breaker;
+mod client;
+mod coverage;
 mod errors;
 mod models;
+mod rate_limit;
 mod requests;
-
+mod retry;
+mod single_flight;
+mod split;
+mod transport;
+mod verify;
+
+pub const DEFAULT_BASE_URL: &str = "https://services.sia.homeoffice.gov.uk";
+pub const SEARCH_LICENSE_NUM_PATH: &str = "/PublicRegister/SearchPublicRegisterByLicence";
+pub const SEARCH_NAME_PATH: &str = "/PublicRegister/SearchPublicRegisterBySurname";
 pub const SEARCH_LICENSE_NUM_URL: &str =
     "https://services.sia.homeoffice.gov.uk/PublicRegister/SearchPublicRegisterByLicence";
 pub const SEARCH_NAME_URL: &str =
@@ -15,6 +44,8 @@ pub const SEARCH_NAME_URL: &str =
 
 /// Search for a license by either license number or name.
 ///
+/// Uses a fresh [`SiaClient`] for each call; create and reuse a client to pool connections.
+///
 /// # Arguments
 ///
 /// * `query` - A query object that contains the search parameters.
@@ -23,23 +54,13 @@ pub const SEARCH_NAME_URL: &str =
 ///
 /// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
 pub async fn search(query: &Query) -> Result<Vec<LicenseState>, SIAError> {
-    if query.license_no.is_some() {
-        let payload = query.to_search_by_license_payload();
-
-        return requests::request_search_by_license(payload).await;
-    }
-
-    if query.has_any() {
-        let payload = query.to_search_by_name_payload();
-
-        return requests::request_search_by_name(payload).await;
-    }
-
-    Ok(Vec::new())
+    SiaClient::new().search(query).await
 }
 
 /// Search for a license by either license number or name synchronously.
 ///
+/// Uses a fresh [`blocking::SiaClient`] for each call; create and reuse a client to pool connections.
+///
 /// # Arguments
 ///
 /// * `query` - A query object that contains the search parameters.
@@ -49,19 +70,7 @@ pub async fn search(query: &Query) -> Result<Vec<LicenseState>, SIAError> {
 /// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
 #[cfg(feature = "blocking")]
 pub fn search_sync(query: &Query) -> Result<Vec<LicenseState>, SIAError> {
-    if query.license_no.is_some() {
-        let payload = query.to_search_by_license_payload();
-
-        return blocking::request_search_by_license(payload);
-    }
-
-    if query.has_any() {
-        let payload = query.to_search_by_name_payload();
-
-        return blocking::request_search_by_name(payload);
-    }
-
-    Ok(Vec::new())
+    blocking::SiaClient::new().search(query)
 }
 
 #[cfg(test)]
@@ -77,7 +86,7 @@ mod tests {
             .with_last_name("Smith".to_string())
             .with_first_name("John".to_string())
             .with_middle_name("James".to_string())
-            .with_date_of_birth("01/01/1970".to_string())
+            .with_date_of_birth(chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())
             .with_role(LicenseRole::Frontline)
             .with_license_sector(LicenseSector::DoorSupervision);
 
@@ -129,7 +138,7 @@ mod tests {
             .with_last_name("Smith".to_string())
             .with_first_name("John".to_string())
             .with_middle_name("James".to_string())
-            .with_date_of_birth("01/01/1970".to_string())
+            .with_date_of_birth(chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())
             .with_role(LicenseRole::Frontline)
             .with_license_sector(LicenseSector::DoorSupervision);
 
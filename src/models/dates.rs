@@ -0,0 +1,115 @@
+use chrono::{Datelike, NaiveDate};
+
+/// The date formats the register has been seen to use, tried in order.
+const DATE_FORMATS: [&str; 8] = [
+    "%d %B %Y",
+    "%d %b %Y",
+    "%d-%b-%Y",
+    "%B %d, %Y",
+    "%d/%m/%Y",
+    "%d-%m-%Y",
+    "%d.%m.%Y",
+    "%Y-%m-%d",
+];
+
+/// The earliest year accepted. `%Y` takes any number of digits, so a two-digit year such as the `30` in
+/// `01/01/30` would otherwise be read as the year 30.
+pub(crate) const MIN_YEAR: i32 = 1900;
+
+/// Parses a date in any of the formats the register uses, e.g. "01 January 2030", "1 Jan 2030" or "01/01/2030".
+///
+/// Numeric dates are read day first, as the register is a UK site. Dates before [`MIN_YEAR`] are rejected.
+pub(crate) fn parse_date(input: &str) -> Option<NaiveDate> {
+    let input = input.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&input, format).ok())
+        .filter(is_plausible)
+}
+
+/// Checks a parsed date isn't before [`MIN_YEAR`].
+fn is_plausible(date: &NaiveDate) -> bool {
+    date.year() >= MIN_YEAR
+}
+
+/// Extra formats accepted for dates typed in by people, such as `19700101` from spreadsheet exports.
+/// Day first is tried first, except for inputs that look year first; see [`parse_user_date`].
+const COMPACT_DATE_FORMATS: [&str; 2] = ["%d%m%Y", "%Y%m%d"];
+
+/// Parses a date entered by a person, accepting everything [`parse_date`] does plus compact numeric dates.
+///
+/// Eight digits starting with `19` or `20` are read year first, so `20010615` is 15 June 2001 rather
+/// than 20 June 0615. Dates before [`MIN_YEAR`] are rejected.
+pub(crate) fn parse_user_date(input: &str) -> Option<NaiveDate> {
+    parse_date(input).or_else(|| {
+        let input = input.trim();
+        let year_first = input.len() == 8
+            && input.chars().all(|c| c.is_ascii_digit())
+            && (input.starts_with("19") || input.starts_with("20"));
+        let mut formats = COMPACT_DATE_FORMATS;
+        if year_first {
+            formats.reverse();
+        }
+
+        formats
+            .iter()
+            .filter_map(|format| NaiveDate::parse_from_str(input, format).ok())
+            .find(is_plausible)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_formats() {
+        let expected = NaiveDate::from_ymd_opt(2030, 3, 7);
+
+        for input in [
+            "07 March 2030",
+            "7 March 2030",
+            " 07  Mar 2030 ",
+            "07-Mar-2030",
+            "March 7, 2030",
+            "07/03/2030",
+            "7-3-2030",
+            "07.03.2030",
+            "2030-03-07",
+        ] {
+            assert_eq!(parse_date(input), expected, "{}", input);
+        }
+
+        assert_eq!(parse_date(""), None);
+        assert_eq!(parse_date("31/02/2030"), None);
+        assert_eq!(parse_date("soon"), None);
+        assert_eq!(parse_date("01/01/30"), None);
+        assert_eq!(parse_date("1 Jan 0030"), None);
+    }
+
+    #[test]
+    fn test_parse_user_date() {
+        let expected = NaiveDate::from_ymd_opt(1970, 1, 31);
+
+        assert_eq!(parse_user_date("1970-01-31"), expected);
+        assert_eq!(parse_user_date("31/01/1970"), expected);
+        assert_eq!(parse_user_date("31011970"), expected);
+        assert_eq!(parse_user_date("19700131"), expected);
+        assert_eq!(
+            parse_user_date("20010615"),
+            NaiveDate::from_ymd_opt(2001, 6, 15)
+        );
+        assert_eq!(
+            parse_user_date("20050101"),
+            NaiveDate::from_ymd_opt(2005, 1, 1)
+        );
+        assert_eq!(
+            parse_user_date("20011970"),
+            NaiveDate::from_ymd_opt(1970, 1, 20)
+        );
+        assert_eq!(parse_user_date("1970"), None);
+        assert_eq!(parse_user_date("1/1/70"), None);
+        assert_eq!(parse_user_date("01011870"), None);
+    }
+}
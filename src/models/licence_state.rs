@@ -1,8 +1,13 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 use chrono::{NaiveDate, TimeDelta};
 use log::warn;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::coverage::Activity;
+use crate::models::{LicenseNumber, ParseReport};
 
 /// Represents the state of a license.
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -11,38 +16,148 @@ pub struct LicenseState {
     pub first_name: String,
     /// The last name of the license holder.
     pub last_name: String,
-    /// The license number.
-    pub license_number: String,
+    /// The license number, or `None` if the register showed one that isn't valid.
+    pub license_number: Option<LicenseNumber>,
+    /// The license number exactly as the register showed it.
+    #[serde(default)]
+    pub raw_license_number: String,
     /// The role of this license
     pub role: LicenseRole,
     /// The sector of this license
     pub sector: LicenseSector,
-    /// The expiry date of the license.
-    pub expiry: NaiveDate,
+    /// The expiry date of the license, or `None` if the register didn't show one that could be read.
+    pub expiry: Option<NaiveDate>,
     /// The status of the license.
-    pub status: String,
+    pub status: LicenseStatus,
     /// The reason for the status.
     pub status_reason: String,
     /// The conditions of the license.
     pub license_conditions: String,
+    /// How this license was read from the register.
+    #[serde(default)]
+    pub parse_report: ParseReport,
 }
 
 impl LicenseState {
-    /// Returns the number of days until the license expires.
-    pub fn expires_in(&self) -> TimeDelta {
-        self.expiry - chrono::Local::now().naive_local().date()
+    /// The license number as plain digits, or the register's text if it isn't a valid license number.
+    pub fn license_number_str(&self) -> &str {
+        match &self.license_number {
+            Some(license_number) => license_number.as_str(),
+            None => &self.raw_license_number,
+        }
+    }
+
+    /// Returns the time until the license expires, or `None` if the expiry date is unknown.
+    pub fn expires_in(&self) -> Option<TimeDelta> {
+        Some(self.expiry? - chrono::Local::now().naive_local().date())
     }
 
-    /// Returns the number of days remaining until the license expires.
-    pub fn remaining_days(&self) -> i64 {
-        self.expires_in().num_days()
+    /// Returns the number of days remaining until the license expires, or `None` if the expiry date is unknown.
+    pub fn remaining_days(&self) -> Option<i64> {
+        self.expires_in().map(|expires_in| expires_in.num_days())
+    }
+
+    /// Checks if the license lets its holder carry out an activity today: it must be active, have a known
+    /// expiry date that hasn't passed, and cover the activity's sector and role. See [`crate::Coverage`] for the reasons it doesn't.
+    pub fn permits(&self, activity: &Activity) -> bool {
+        crate::coverage::problems(self, activity).is_empty()
     }
 }
 
 impl Display for LicenseState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "First Name: {} | Last Name: {} | License Number: {} | Role: {} | Sector: {} | Expiry: {} | Status: {} | Status Reason: {} | License Conditions: {}",
-               self.first_name, self.last_name, self.license_number, self.role, self.sector, self.expiry, self.status, self.status_reason, self.license_conditions)
+               self.first_name, self.last_name, self.license_number.as_ref().map_or(self.raw_license_number.clone(), |license_number| license_number.to_string()), self.role, self.sector, self.expiry.map_or("Unknown".to_string(), |expiry| expiry.to_string()), self.status, self.status_reason, self.license_conditions)
+    }
+}
+
+/// Represents the status of a license.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub enum LicenseStatus {
+    /// The license is valid and the holder may work in its sector.
+    Active,
+    /// The license has passed its expiry date.
+    Expired,
+    /// The license has been revoked by the SIA.
+    Revoked,
+    /// The license has been suspended by the SIA.
+    Suspended,
+    /// The holder has given up the license.
+    Surrendered,
+    /// The license has been cancelled.
+    Cancelled,
+    /// The license has been withdrawn.
+    Withdrawn,
+    /// An unknown status - Used as a fallback, holding the text shown by the register.
+    Unknown(String),
+}
+
+impl LicenseStatus {
+    /// Checks if the status allows the holder to work under the license.
+    pub fn is_valid_to_work(&self) -> bool {
+        matches!(self, LicenseStatus::Active)
+    }
+
+    /// Checks if the license has expired.
+    pub fn is_expired(&self) -> bool {
+        matches!(self, LicenseStatus::Expired)
+    }
+
+    /// Checks if the license is suspended, and so may become active again.
+    pub fn is_suspended(&self) -> bool {
+        matches!(self, LicenseStatus::Suspended)
+    }
+
+    /// Checks if the license has been permanently ended by revocation, surrender, cancellation or withdrawal.
+    pub fn is_terminated(&self) -> bool {
+        matches!(
+            self,
+            LicenseStatus::Revoked
+                | LicenseStatus::Surrendered
+                | LicenseStatus::Cancelled
+                | LicenseStatus::Withdrawn
+        )
+    }
+
+    /// Checks if the status wasn't recognised.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, LicenseStatus::Unknown(_))
+    }
+}
+
+impl Display for LicenseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LicenseStatus::Active => write!(f, "Active"),
+            LicenseStatus::Expired => write!(f, "Expired"),
+            LicenseStatus::Revoked => write!(f, "Revoked"),
+            LicenseStatus::Suspended => write!(f, "Suspended"),
+            LicenseStatus::Surrendered => write!(f, "Surrendered"),
+            LicenseStatus::Cancelled => write!(f, "Cancelled"),
+            LicenseStatus::Withdrawn => write!(f, "Withdrawn"),
+            LicenseStatus::Unknown(status) => write!(f, "{}", status),
+        }
+    }
+}
+
+impl From<&String> for LicenseStatus {
+    fn from(s: &String) -> Self {
+        let mut normalised = s.replace(|c: char| !c.is_alphanumeric(), "");
+        normalised = normalised.to_lowercase();
+
+        match normalised.as_str() {
+            "active" | "valid" => LicenseStatus::Active,
+            "expired" => LicenseStatus::Expired,
+            "revoked" => LicenseStatus::Revoked,
+            "suspended" => LicenseStatus::Suspended,
+            "surrendered" => LicenseStatus::Surrendered,
+            "cancelled" | "canceled" => LicenseStatus::Cancelled,
+            "withdrawn" => LicenseStatus::Withdrawn,
+            _ => {
+                warn!("Unknown status: {} - Please report this.", s);
+                LicenseStatus::Unknown(s.trim().to_string())
+            }
+        }
     }
 }
 
@@ -53,8 +168,56 @@ pub enum LicenseRole {
     Frontline,
     /// A license required for those who manage, supervise, or employ individuals who engage in licensable activities.
     NonFrontline,
-    /// An unknown role - Used as a fallback.
-    Unknown,
+    /// An unknown role - Used as a fallback, holding the text shown by the register.
+    Unknown(String),
+}
+
+/// A string that isn't a known [`LicenseRole`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Unknown license role: {0:?}")]
+pub struct ParseLicenseRoleError(pub String);
+
+impl LicenseRole {
+    /// Every role a license can be issued for, in the order the register lists them.
+    pub const ALL: [LicenseRole; 2] = [LicenseRole::Frontline, LicenseRole::NonFrontline];
+
+    /// A stable identifier for the role, e.g. `non_frontline`, suitable for storing or for a UI.
+    /// It never changes between releases and parses back with [`str::parse`], except for `Unknown`,
+    /// whose identifier is always `unknown` and doesn't parse.
+    pub fn id(&self) -> &str {
+        match self {
+            LicenseRole::Frontline => "frontline",
+            LicenseRole::NonFrontline => "non_frontline",
+            LicenseRole::Unknown(_) => "unknown",
+        }
+    }
+
+    /// The value the register's search form expects in its `Role` field.
+    pub fn form_value(&self) -> &str {
+        match self {
+            LicenseRole::Frontline => "Front Line",
+            LicenseRole::NonFrontline => "Non Front Line",
+            LicenseRole::Unknown(role) => role,
+        }
+    }
+
+    /// Checks if the role wasn't recognised.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, LicenseRole::Unknown(_))
+    }
+
+    /// Checks if a license with this role allows work in the `required` role.
+    ///
+    /// A front line license also covers non-front line work, but a non-front line license covers no front line work.
+    pub fn covers(&self, required: &LicenseRole) -> bool {
+        matches!(
+            (self, required),
+            (
+                LicenseRole::Frontline,
+                LicenseRole::Frontline | LicenseRole::NonFrontline
+            ) | (LicenseRole::NonFrontline, LicenseRole::NonFrontline)
+        )
+    }
 }
 
 impl Display for LicenseRole {
@@ -62,27 +225,33 @@ impl Display for LicenseRole {
         match self {
             LicenseRole::Frontline => write!(f, "Front Line"),
             LicenseRole::NonFrontline => write!(f, "Non Front Line"),
-            _ => write!(f, "Unknown Role"),
+            LicenseRole::Unknown(role) => write!(f, "{}", role),
         }
     }
 }
 
-impl From<&String> for LicenseRole {
-    fn from(s: &String) -> Self {
-        let mut s = s.replace(|c: char| !c.is_alphanumeric(), "");
-        s = s.to_lowercase();
+impl FromStr for LicenseRole {
+    type Err = ParseLicenseRoleError;
 
-        match s.as_str() {
-            "frontline" => LicenseRole::Frontline,
-            "nonfrontline" => LicenseRole::NonFrontline,
-            _ => {
-                warn!("Unknown role: {} - Please report this.", s);
-                LicenseRole::Unknown
-            }
+    /// Parses a role from its display text, identifier or form value, ignoring case and punctuation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalise(s).as_str() {
+            "frontline" => Ok(LicenseRole::Frontline),
+            "nonfrontline" => Ok(LicenseRole::NonFrontline),
+            _ => Err(ParseLicenseRoleError(s.trim().to_string())),
         }
     }
 }
 
+impl From<&String> for LicenseRole {
+    fn from(s: &String) -> Self {
+        s.parse().unwrap_or_else(|_| {
+            warn!("Unknown role: {} - Please report this.", s);
+            LicenseRole::Unknown(s.trim().to_string())
+        })
+    }
+}
+
 /// Represents the sector of a license.
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum LicenseSector {
@@ -102,44 +271,229 @@ pub enum LicenseSector {
     KeyHolding,
     /// No specific sector.
     NoSector,
-    /// An unknown sector - Used as a fallback.
-    Unknown,
+    /// An unknown sector - Used as a fallback, holding the text shown by the register.
+    Unknown(String),
+}
+
+/// A string that isn't a known [`LicenseSector`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Unknown license sector: {0:?}")]
+pub struct ParseLicenseSectorError(pub String);
+
+impl LicenseSector {
+    /// Every sector a license can be issued for, in the order the register lists them.
+    pub const ALL: [LicenseSector; 7] = [
+        LicenseSector::CashInTransit,
+        LicenseSector::CloseProtection,
+        LicenseSector::DoorSupervision,
+        LicenseSector::PublicSpaceSurveillance,
+        LicenseSector::SecurityGuard,
+        LicenseSector::VehicleImmobilisation,
+        LicenseSector::KeyHolding,
+    ];
+
+    /// A stable identifier for the sector, e.g. `door_supervision`, suitable for storing or for a UI.
+    /// It never changes between releases and parses back with [`str::parse`], except for `Unknown`,
+    /// whose identifier is always `unknown` and doesn't parse.
+    pub fn id(&self) -> &str {
+        match self {
+            LicenseSector::CashInTransit => "cash_in_transit",
+            LicenseSector::CloseProtection => "close_protection",
+            LicenseSector::DoorSupervision => "door_supervision",
+            LicenseSector::PublicSpaceSurveillance => "public_space_surveillance",
+            LicenseSector::SecurityGuard => "security_guard",
+            LicenseSector::VehicleImmobilisation => "vehicle_immobilisation",
+            LicenseSector::KeyHolding => "key_holding",
+            LicenseSector::NoSector => "no_sector",
+            LicenseSector::Unknown(_) => "unknown",
+        }
+    }
+
+    /// The value the register's search form expects in its `LicenseSector` field.
+    /// `NoSector` leaves the field empty, matching any sector.
+    pub fn form_value(&self) -> &str {
+        match self {
+            LicenseSector::CashInTransit => "Cash and Valuables in Transit",
+            LicenseSector::CloseProtection => "Close Protection",
+            LicenseSector::DoorSupervision => "Door Supervision",
+            LicenseSector::PublicSpaceSurveillance => "Public Space Surveillance (CCTV)",
+            LicenseSector::SecurityGuard => "Security Guard",
+            LicenseSector::VehicleImmobilisation => "Vehicle Immobilisation",
+            LicenseSector::KeyHolding => "Key Holding",
+            LicenseSector::NoSector => "",
+            LicenseSector::Unknown(sector) => sector,
+        }
+    }
+
+    /// Checks if the sector wasn't recognised.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, LicenseSector::Unknown(_))
+    }
+
+    /// The sectors a license in this sector allows work in, including its own.
+    ///
+    /// Follows the SIA's licence integration: Close Protection also covers Door Supervision, Door Supervision also
+    /// covers Security Guarding, and Security Guarding also covers Key Holding. Other sectors cover only themselves,
+    /// and `NoSector` and unknown sectors cover nothing.
+    pub fn covered_sectors(&self) -> &'static [LicenseSector] {
+        const CLOSE_PROTECTION: &[LicenseSector] = &[
+            LicenseSector::CloseProtection,
+            LicenseSector::DoorSupervision,
+            LicenseSector::SecurityGuard,
+            LicenseSector::KeyHolding,
+        ];
+        const DOOR_SUPERVISION: &[LicenseSector] = &[
+            LicenseSector::DoorSupervision,
+            LicenseSector::SecurityGuard,
+            LicenseSector::KeyHolding,
+        ];
+        const SECURITY_GUARD: &[LicenseSector] =
+            &[LicenseSector::SecurityGuard, LicenseSector::KeyHolding];
+
+        match self {
+            LicenseSector::CloseProtection => CLOSE_PROTECTION,
+            LicenseSector::DoorSupervision => DOOR_SUPERVISION,
+            LicenseSector::SecurityGuard => SECURITY_GUARD,
+            LicenseSector::CashInTransit => &[LicenseSector::CashInTransit],
+            LicenseSector::PublicSpaceSurveillance => &[LicenseSector::PublicSpaceSurveillance],
+            LicenseSector::VehicleImmobilisation => &[LicenseSector::VehicleImmobilisation],
+            LicenseSector::KeyHolding => &[LicenseSector::KeyHolding],
+            LicenseSector::NoSector | LicenseSector::Unknown(_) => &[],
+        }
+    }
+
+    /// Checks if a license in this sector allows work in the `required` sector.
+    pub fn covers(&self, required: &LicenseSector) -> bool {
+        self.covered_sectors().contains(required)
+    }
 }
 
 impl Display for LicenseSector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            LicenseSector::CashInTransit => write!(f, "Cash and Valuables in Transit"),
-            LicenseSector::CloseProtection => write!(f, "Close Protection"),
-            LicenseSector::DoorSupervision => write!(f, "Door Supervision"),
-            LicenseSector::PublicSpaceSurveillance => write!(f, "Public Space Surveillance (CCTV)"),
-            LicenseSector::SecurityGuard => write!(f, "Security Guard"),
-            LicenseSector::VehicleImmobilisation => write!(f, "Vehicle Immobilisation"),
-            LicenseSector::KeyHolding => write!(f, "Key Holding"),
-            LicenseSector::NoSector => write!(f, ""),
-            _ => write!(f, "Unknown Sector"),
+            LicenseSector::NoSector => write!(f, "No Sector"),
+            sector => write!(f, "{}", sector.form_value()),
+        }
+    }
+}
+
+impl FromStr for LicenseSector {
+    type Err = ParseLicenseSectorError;
+
+    /// Parses a sector from its display text, identifier or form value, ignoring case and punctuation.
+    /// An empty string is `NoSector`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match normalise(s).as_str() {
+            "cashandvaluablesintransit" | "cashintransit" => Ok(LicenseSector::CashInTransit),
+            "closeprotection" => Ok(LicenseSector::CloseProtection),
+            "doorsupervision" => Ok(LicenseSector::DoorSupervision),
+            "publicspacesurveillancecctv" | "publicspacesurveillance" => {
+                Ok(LicenseSector::PublicSpaceSurveillance)
+            }
+            "securityguard" | "securityguarding" => Ok(LicenseSector::SecurityGuard),
+            "vehicleimmobilisation" => Ok(LicenseSector::VehicleImmobilisation),
+            "keyholding" => Ok(LicenseSector::KeyHolding),
+            "" | "nosector" => Ok(LicenseSector::NoSector),
+            _ => Err(ParseLicenseSectorError(s.trim().to_string())),
         }
     }
 }
 
 impl From<&String> for LicenseSector {
     fn from(s: &String) -> Self {
-        let mut s = s.replace(|c: char| !c.is_alphanumeric(), "");
-        s = s.to_lowercase();
-        match s.as_str() {
-            "cashandvaluablesintransit" => LicenseSector::CashInTransit,
-            "cashintransit" => LicenseSector::CashInTransit,
-            "closeprotection" => LicenseSector::CloseProtection,
-            "doorsupervision" => LicenseSector::DoorSupervision,
-            "publicspacesurveillancecctv" => LicenseSector::PublicSpaceSurveillance,
-            "securityguard" => LicenseSector::SecurityGuard,
-            "vehicleimmobilisation" => LicenseSector::VehicleImmobilisation,
-            "keyholding" => LicenseSector::KeyHolding,
-            "" => LicenseSector::NoSector,
-            _ => {
-                warn!("Unknown sector: {} - Please report this.", s);
-                LicenseSector::Unknown
-            }
+        s.parse().unwrap_or_else(|_| {
+            warn!("Unknown sector: {} - Please report this.", s);
+            LicenseSector::Unknown(s.trim().to_string())
+        })
+    }
+}
+
+/// Lower-cases a value and drops everything but letters and digits, so `Non-Front Line` matches `non_frontline`.
+fn normalise(s: &str) -> String {
+    s.replace(|c: char| !c.is_alphanumeric(), "").to_lowercase()
+}
+
+#[cfg(test)]
+impl LicenseState {
+    /// An active front line Door Supervision license held by John Smith, in date until 2999.
+    /// Tests change the fields they care about with struct update syntax.
+    pub(crate) fn example() -> Self {
+        Self {
+            first_name: "JOHN".to_string(),
+            last_name: "SMITH".to_string(),
+            license_number: "1234567890123456".parse().ok(),
+            raw_license_number: "1234567890123456".to_string(),
+            role: LicenseRole::Frontline,
+            sector: LicenseSector::DoorSupervision,
+            expiry: NaiveDate::from_ymd_opt(2999, 1, 1),
+            status: LicenseStatus::Active,
+            status_reason: String::new(),
+            license_conditions: String::new(),
+            parse_report: ParseReport::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_from_string() {
+        assert_eq!(
+            LicenseStatus::from(&" ACTIVE ".to_string()),
+            LicenseStatus::Active
+        );
+        assert_eq!(
+            LicenseStatus::from(&"Cancelled.".to_string()),
+            LicenseStatus::Cancelled
+        );
+        assert_eq!(
+            LicenseStatus::from(&"Under review".to_string()),
+            LicenseStatus::Unknown("Under review".to_string())
+        );
+
+        assert!(LicenseStatus::Active.is_valid_to_work());
+        assert!(!LicenseStatus::Suspended.is_valid_to_work());
+        assert!(LicenseStatus::Revoked.is_terminated());
+        assert!(!LicenseStatus::Unknown("Active pending".to_string()).is_valid_to_work());
+    }
+
+    #[test]
+    fn test_roles_and_sectors_round_trip() {
+        for role in LicenseRole::ALL {
+            assert_eq!(role.id().parse::<LicenseRole>(), Ok(role.clone()));
+            assert_eq!(role.to_string().parse::<LicenseRole>(), Ok(role.clone()));
+            assert_eq!(role.form_value().parse::<LicenseRole>(), Ok(role));
+        }
+        for sector in LicenseSector::ALL
+            .into_iter()
+            .chain([LicenseSector::NoSector])
+        {
+            assert_eq!(sector.id().parse::<LicenseSector>(), Ok(sector.clone()));
+            assert_eq!(
+                sector.to_string().parse::<LicenseSector>(),
+                Ok(sector.clone())
+            );
+            assert_eq!(sector.form_value().parse::<LicenseSector>(), Ok(sector));
         }
+
+        assert_eq!(LicenseSector::NoSector.to_string(), "No Sector");
+        assert!(LicenseSector::Unknown("Dog Handling".to_string())
+            .id()
+            .parse::<LicenseSector>()
+            .is_err());
+        assert!(LicenseSector::DoorSupervision.covers(&LicenseSector::KeyHolding));
+        assert!(!LicenseSector::SecurityGuard.covers(&LicenseSector::DoorSupervision));
+        assert!(LicenseRole::Frontline.covers(&LicenseRole::NonFrontline));
+        assert!(!LicenseRole::NonFrontline.covers(&LicenseRole::Frontline));
+        assert_eq!(
+            "Trainee".parse::<LicenseRole>(),
+            Err(ParseLicenseRoleError("Trainee".to_string()))
+        );
+        assert_eq!(
+            LicenseSector::from(&" Dog Handling ".to_string()),
+            LicenseSector::Unknown("Dog Handling".to_string())
+        );
     }
 }
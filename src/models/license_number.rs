@@ -0,0 +1,154 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// The number of digits in an SIA license number.
+const LICENSE_NUMBER_LENGTH: usize = 16;
+
+/// A validated SIA license number: 16 digits.
+///
+/// Parsing accepts the styles people commonly type, ignoring spaces and dashes, so `1234567890123456`,
+/// `1234 5678 9012 3456` and `1234-5678-9012-3456` are all the same number.
+/// It displays grouped in fours, as the register shows it, and serializes as the plain digits.
+///
+/// # Example
+///
+/// ```
+/// use sia_rs::LicenseNumber;
+///
+/// let license_no: LicenseNumber = "1234-5678-9012-3456".parse().unwrap();
+///
+/// assert_eq!(license_no.as_str(), "1234567890123456");
+/// assert_eq!(license_no.to_string(), "1234 5678 9012 3456");
+/// assert!("123456".parse::<LicenseNumber>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LicenseNumber(String);
+
+/// Why a string isn't a valid [`LicenseNumber`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseLicenseNumberError {
+    #[error("License number contains an invalid character: {0:?}")]
+    InvalidCharacter(char),
+    #[error("License number has {0} digits, expected 16.")]
+    WrongLength(usize),
+}
+
+impl LicenseNumber {
+    /// Parses a license number, ignoring spaces and dashes.
+    pub fn new(input: &str) -> Result<Self, ParseLicenseNumberError> {
+        let mut digits = String::with_capacity(LICENSE_NUMBER_LENGTH);
+
+        for c in input.trim().chars() {
+            match c {
+                '0'..='9' => digits.push(c),
+                ' ' | '-' => {}
+                _ => return Err(ParseLicenseNumberError::InvalidCharacter(c)),
+            }
+        }
+
+        if digits.len() != LICENSE_NUMBER_LENGTH {
+            return Err(ParseLicenseNumberError::WrongLength(digits.len()));
+        }
+
+        Ok(Self(digits))
+    }
+
+    /// The license number as plain digits, the form the register's search expects.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for LicenseNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let groups: Vec<&str> = (0..LICENSE_NUMBER_LENGTH)
+            .step_by(4)
+            .map(|start| &self.0[start..start + 4])
+            .collect();
+        write!(f, "{}", groups.join(" "))
+    }
+}
+
+impl FromStr for LicenseNumber {
+    type Err = ParseLicenseNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl AsRef<str> for LicenseNumber {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<LicenseNumber> for String {
+    fn from(license_no: LicenseNumber) -> Self {
+        license_no.0
+    }
+}
+
+impl Serialize for LicenseNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LicenseNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let input = String::deserialize(deserializer)?;
+        Self::new(&input).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_common_styles() {
+        for input in [
+            "1234567890123456",
+            "1234 5678 9012 3456",
+            "1234-5678-9012-3456",
+            " 1234 - 5678 - 9012 - 3456 ",
+        ] {
+            let license_no = LicenseNumber::new(input).unwrap();
+            assert_eq!(license_no.as_str(), "1234567890123456");
+            assert_eq!(license_no.to_string(), "1234 5678 9012 3456");
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed_numbers() {
+        assert_eq!(
+            LicenseNumber::new("123456"),
+            Err(ParseLicenseNumberError::WrongLength(6))
+        );
+        assert_eq!(
+            LicenseNumber::new("1234 5678 9012 34567"),
+            Err(ParseLicenseNumberError::WrongLength(17))
+        );
+        assert_eq!(
+            LicenseNumber::new("1234/5678/9012/3456"),
+            Err(ParseLicenseNumberError::InvalidCharacter('/'))
+        );
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let license_no = LicenseNumber::new("1234 5678 9012 3456").unwrap();
+        let json = serde_json::to_string(&license_no).unwrap();
+
+        assert_eq!(json, "\"1234567890123456\"");
+        assert_eq!(
+            serde_json::from_str::<LicenseNumber>(&json).unwrap(),
+            license_no
+        );
+        assert!(serde_json::from_str::<LicenseNumber>("\"1234\"").is_err());
+    }
+}
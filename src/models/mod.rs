@@ -1,6 +1,18 @@
-pub use licence_state::{LicenseRole, LicenseSector, LicenseState};
-pub use query::Query;
+pub use licence_state::{
+    LicenseRole, LicenseSector, LicenseState, LicenseStatus, ParseLicenseRoleError,
+    ParseLicenseSectorError,
+};
+pub use license_number::{LicenseNumber, ParseLicenseNumberError};
+pub use parse_report::{LicenseField, ParseReport, ParseStrategy};
+pub use query::{Query, QueryField, QueryProblem, ToQuery};
+pub use search_outcome::SearchOutcome;
 
+mod dates;
 mod licence_state;
+mod license_number;
+mod parse_report;
 pub mod payloads;
 mod query;
+mod search_outcome;
+
+pub(crate) use dates::{parse_date, parse_user_date, MIN_YEAR};
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// A field of a [`crate::LicenseState`] read from the register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LicenseField {
+    FirstName,
+    LastName,
+    LicenseNumber,
+    Role,
+    Sector,
+    Expiry,
+    Status,
+    StatusReason,
+    LicenseConditions,
+}
+
+impl LicenseField {
+    /// Every field, in the order they appear on the register.
+    pub const ALL: [LicenseField; 9] = [
+        LicenseField::FirstName,
+        LicenseField::LastName,
+        LicenseField::LicenseNumber,
+        LicenseField::Role,
+        LicenseField::Sector,
+        LicenseField::Expiry,
+        LicenseField::Status,
+        LicenseField::StatusReason,
+        LicenseField::LicenseConditions,
+    ];
+}
+
+impl Display for LicenseField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LicenseField::FirstName => write!(f, "first name"),
+            LicenseField::LastName => write!(f, "last name"),
+            LicenseField::LicenseNumber => write!(f, "license number"),
+            LicenseField::Role => write!(f, "role"),
+            LicenseField::Sector => write!(f, "sector"),
+            LicenseField::Expiry => write!(f, "expiry date"),
+            LicenseField::Status => write!(f, "status"),
+            LicenseField::StatusReason => write!(f, "status reason"),
+            LicenseField::LicenseConditions => write!(f, "license conditions"),
+        }
+    }
+}
+
+/// How a field's value was located on the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParseStrategy {
+    /// Found next to its visible label, e.g. "Licence number".
+    Label,
+    /// Found by its position in the markup, used when no label matched.
+    Positional,
+}
+
+/// Describes how a license was read from the register's page, and anywhere a value had to be guessed.
+///
+/// A license with a complete report holds exactly what the register showed. Otherwise the fields listed
+/// here hold empty strings, `None` or `Unknown` variants rather than real values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParseReport {
+    /// The strategy used for each field that was found.
+    pub strategies: BTreeMap<LicenseField, ParseStrategy>,
+    /// Fields that couldn't be found on the page.
+    pub missing: Vec<LicenseField>,
+    /// Fields holding a default value, such as an empty string, instead of one read from the page.
+    pub defaulted: Vec<LicenseField>,
+    /// Text that was found but not understood, such as an unrecognised role, sector or status.
+    pub unknown_values: BTreeMap<LicenseField, String>,
+}
+
+impl ParseReport {
+    /// Returns how a field was found, or `None` if it wasn't.
+    pub fn strategy(&self, field: LicenseField) -> Option<ParseStrategy> {
+        self.strategies.get(&field).copied()
+    }
+
+    /// Checks if every field was found and understood.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.defaulted.is_empty() && self.unknown_values.is_empty()
+    }
+
+    /// Returns the first field that was missing, defaulted or not understood.
+    pub fn first_gap(&self) -> Option<LicenseField> {
+        LicenseField::ALL.into_iter().find(|field| {
+            self.missing.contains(field)
+                || self.defaulted.contains(field)
+                || self.unknown_values.contains_key(field)
+        })
+    }
+
+    /// Records a field that wasn't on the page.
+    pub(crate) fn record_missing(&mut self, field: LicenseField) {
+        self.missing.push(field);
+    }
+
+    /// Records text that wasn't understood but was kept as it was.
+    pub(crate) fn record_unknown(&mut self, field: LicenseField, value: &str) {
+        self.unknown_values.insert(field, value.to_string());
+    }
+
+    /// Checks if any field had to fall back to its position in the markup.
+    pub fn used_fallback(&self) -> bool {
+        self.strategies
+            .values()
+            .any(|strategy| *strategy == ParseStrategy::Positional)
+    }
+}
@@ -1,7 +1,18 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+use thiserror::Error;
+
 use crate::models::payloads::{SearchByLicense, SearchByName};
-use crate::models::{LicenseRole, LicenseSector};
+use crate::models::{
+    parse_user_date, LicenseNumber, LicenseRole, LicenseSector, ParseLicenseNumberError, MIN_YEAR,
+};
 use crate::{LicenseState, SIAError};
 
+/// The format the register's search form expects dates of birth in.
+const DATE_OF_BIRTH_FORMAT: &str = "%d/%m/%Y";
+
 /// A query object that contains the search parameters.
 /// Follows the builder pattern.
 ///
@@ -15,15 +26,64 @@ use crate::{LicenseState, SIAError};
 ///
 /// let result = search(&query);
 /// ```
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct Query {
     pub first_name: Option<String>,
     pub middle_name: Option<String>,
     pub last_name: Option<String>,
-    pub date_of_birth: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
     pub role: Option<String>,
     pub license_sector: Option<String>,
-    pub license_no: Option<String>,
+    pub license_no: Option<LicenseNumber>,
+    /// License number text that isn't a valid [`LicenseNumber`], kept so [`Query::validate`] can report why.
+    pub invalid_license_no: Option<String>,
+}
+
+/// A field of a [`Query`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryField {
+    FirstName,
+    MiddleName,
+    LastName,
+    DateOfBirth,
+    Role,
+    LicenseSector,
+    LicenseNumber,
+}
+
+impl Display for QueryField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryField::FirstName => write!(f, "first name"),
+            QueryField::MiddleName => write!(f, "middle name"),
+            QueryField::LastName => write!(f, "last name"),
+            QueryField::DateOfBirth => write!(f, "date of birth"),
+            QueryField::Role => write!(f, "role"),
+            QueryField::LicenseSector => write!(f, "license sector"),
+            QueryField::LicenseNumber => write!(f, "license number"),
+        }
+    }
+}
+
+/// Something wrong with a [`Query`], found by [`Query::validate`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryProblem {
+    #[error("there is nothing to search for")]
+    Empty,
+    #[error("{0} is required when searching by name")]
+    MissingField(QueryField),
+    #[error("{0} can't be combined with a license number")]
+    Conflict(QueryField),
+    #[error("{0} is blank")]
+    Blank(QueryField),
+    #[error("{0}")]
+    InvalidLicenseNumber(ParseLicenseNumberError),
+    #[error("date of birth {0} is in the future")]
+    FutureDateOfBirth(NaiveDate),
+    #[error("date of birth {0} is before {min_year}", min_year = MIN_YEAR)]
+    ImplausibleDateOfBirth(NaiveDate),
+    #[error("{field} {value:?} isn't recognised")]
+    UnrecognisedValue { field: QueryField, value: String },
 }
 
 impl Query {
@@ -50,35 +110,77 @@ impl Query {
     }
 
     /// Sets the date of birth of the license holder.
-    pub fn with_date_of_birth(mut self, date_of_birth: String) -> Self {
+    pub fn with_date_of_birth(mut self, date_of_birth: NaiveDate) -> Self {
         self.date_of_birth = Some(date_of_birth);
         self
     }
 
+    /// Parses a date of birth typed in by a person, e.g. `1970-01-31`, `31/01/1970` or `31 January 1970`.
+    /// Numeric dates are read day first unless they start with the year.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sia_rs::Query;
+    ///
+    /// let date_of_birth = Query::parse_date_of_birth("1970-01-31").unwrap();
+    /// let query = Query::new()
+    ///     .with_last_name("Smith".to_string())
+    ///     .with_date_of_birth(date_of_birth);
+    /// ```
+    pub fn parse_date_of_birth(input: &str) -> Option<NaiveDate> {
+        parse_user_date(input)
+    }
+
     /// Sets the role of the license.
     pub fn with_role(mut self, role: LicenseRole) -> Self {
-        self.role = Some(role.to_string());
+        self.role = Some(role.form_value().to_string());
         self
     }
 
-    /// Sets the sector of the license.
+    /// Sets the sector of the license. `NoSector` clears it, so any sector matches.
     pub fn with_license_sector(mut self, license_sector: LicenseSector) -> Self {
-        self.license_sector = Some(license_sector.to_string());
+        self.license_sector = match license_sector {
+            LicenseSector::NoSector => None,
+            sector => Some(sector.form_value().to_string()),
+        };
         self
     }
 
-    /// Sets the license number.
-    pub fn with_license_no(mut self, license_no: String) -> Self {
-        self.license_no = Some(license_no);
-        self
+    /// Sets the license number, parsing it as a [`LicenseNumber`].
+    /// Text that doesn't parse is kept in `invalid_license_no`, and the query fails validation.
+    pub fn with_license_no(self, license_no: String) -> Self {
+        match LicenseNumber::new(&license_no) {
+            Ok(license_no) => self.with_license(license_no),
+            Err(_) => Self {
+                license_no: None,
+                invalid_license_no: Some(license_no),
+                ..self
+            },
+        }
     }
 
-    /// Checks if any search parameters are set.
-    pub fn with_license_number(mut self, license_no: String) -> Self {
+    /// Sets the license number from an already validated [`LicenseNumber`].
+    pub fn with_license(mut self, license_no: LicenseNumber) -> Self {
         self.license_no = Some(license_no);
+        self.invalid_license_no = None;
         self
     }
 
+    /// Sets the license number. The same as [`Query::with_license_no`].
+    pub fn with_license_number(self, license_no: String) -> Self {
+        self.with_license_no(license_no)
+    }
+
+    /// Returns the license number if one is set, or why the text given isn't a valid one.
+    pub fn license_number(&self) -> Option<Result<LicenseNumber, ParseLicenseNumberError>> {
+        match (&self.license_no, &self.invalid_license_no) {
+            (Some(license_no), _) => Some(Ok(license_no.clone())),
+            (None, Some(invalid)) => Some(LicenseNumber::new(invalid)),
+            (None, None) => None,
+        }
+    }
+
     /// Checks if any search parameters are set.
     pub fn has_any(&self) -> bool {
         self.first_name.is_some()
@@ -87,7 +189,105 @@ impl Query {
             || self.date_of_birth.is_some()
             || self.role.is_some()
             || self.license_sector.is_some()
-            || self.license_no.is_some()
+            || self.license_number().is_some()
+    }
+
+    /// Checks the query can be sent to the register as it is.
+    ///
+    /// A license number search must have a valid license number and nothing else, as the register
+    /// ignores name fields when searching by number. A name search must have a last name.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), SIAError>` - `SIAError::InvalidQuery` listing every problem found, if there are any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sia_rs::Query;
+    ///
+    /// assert!(Query::new().with_first_name("John".to_string()).validate().is_err());
+    /// assert!(Query::new().with_last_name("Smith".to_string()).validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<(), SIAError> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SIAError::InvalidQuery { problems })
+        }
+    }
+
+    fn problems(&self) -> Vec<QueryProblem> {
+        let text_fields = [
+            (QueryField::FirstName, &self.first_name),
+            (QueryField::MiddleName, &self.middle_name),
+            (QueryField::LastName, &self.last_name),
+            (QueryField::Role, &self.role),
+            (QueryField::LicenseSector, &self.license_sector),
+        ];
+        let mut problems = Vec::new();
+
+        if !self.has_any() {
+            problems.push(QueryProblem::Empty);
+            return problems;
+        }
+
+        for (field, value) in text_fields {
+            // An empty sector is the form value of `NoSector`, matching any sector.
+            if field == QueryField::LicenseSector {
+                continue;
+            }
+            if value.as_ref().is_some_and(|value| value.trim().is_empty()) {
+                problems.push(QueryProblem::Blank(field));
+            }
+        }
+
+        if let Some(license_no) = self.license_number() {
+            if let Err(err) = license_no {
+                problems.push(QueryProblem::InvalidLicenseNumber(err));
+            }
+
+            let name_fields = text_fields
+                .iter()
+                .filter(|(_, value)| value.is_some())
+                .map(|(field, _)| *field)
+                .chain(self.date_of_birth.map(|_| QueryField::DateOfBirth));
+            problems.extend(name_fields.map(QueryProblem::Conflict));
+            return problems;
+        }
+
+        if self.last_name.is_none() {
+            problems.push(QueryProblem::MissingField(QueryField::LastName));
+        }
+
+        if let Some(date_of_birth) = self.date_of_birth {
+            if date_of_birth > chrono::Local::now().date_naive() {
+                problems.push(QueryProblem::FutureDateOfBirth(date_of_birth));
+            } else if date_of_birth.year() < MIN_YEAR {
+                problems.push(QueryProblem::ImplausibleDateOfBirth(date_of_birth));
+            }
+        }
+
+        if let Some(role) = self.role.as_ref().filter(|role| !role.trim().is_empty()) {
+            if role.parse::<LicenseRole>().is_err() {
+                problems.push(QueryProblem::UnrecognisedValue {
+                    field: QueryField::Role,
+                    value: role.clone(),
+                });
+            }
+        }
+
+        if let Some(sector) = &self.license_sector {
+            if sector.parse::<LicenseSector>().is_err() {
+                problems.push(QueryProblem::UnrecognisedValue {
+                    field: QueryField::LicenseSector,
+                    value: sector.clone(),
+                });
+            }
+        }
+
+        problems
     }
 
     /// Builds the query object.
@@ -101,16 +301,31 @@ impl Query {
             last_name: self.last_name.clone().unwrap_or("".to_string()),
             first_name: self.first_name.clone().unwrap_or("".to_string()),
             middle_name: self.middle_name.clone().unwrap_or("".to_string()),
-            dob: self.date_of_birth.clone().unwrap_or("".to_string()),
-            role: self.role.clone().unwrap_or("".to_string()),
-            license_sector: self.license_sector.clone().unwrap_or("".to_string()),
+            dob: self
+                .date_of_birth
+                .map(|date_of_birth| date_of_birth.format(DATE_OF_BIRTH_FORMAT).to_string())
+                .unwrap_or_default(),
+            role: self
+                .role
+                .as_deref()
+                .map(|role| form_value::<LicenseRole>(role, LicenseRole::form_value))
+                .unwrap_or_default(),
+            license_sector: self
+                .license_sector
+                .as_deref()
+                .map(|sector| form_value::<LicenseSector>(sector, LicenseSector::form_value))
+                .unwrap_or_default(),
         }
     }
 
     /// Converts the query object to a SearchByLicense payload.
     pub fn to_search_by_license_payload(&self) -> SearchByLicense {
         SearchByLicense {
-            license_no: self.license_no.clone().unwrap_or("".to_string()),
+            license_no: match (&self.license_no, &self.invalid_license_no) {
+                (Some(license_no), _) => license_no.as_str().to_string(),
+                (None, Some(invalid)) => invalid.clone(),
+                (None, None) => String::new(),
+            },
         }
     }
 
@@ -130,12 +345,152 @@ impl Query {
     }
 }
 
+/// Anything that can be turned into a search, such as a [`Query`] or a license number.
+/// Used by the bulk search functions so results can be paired with the input that produced them.
+pub trait ToQuery {
+    fn to_query(&self) -> Query;
+}
+
+impl ToQuery for Query {
+    fn to_query(&self) -> Query {
+        self.clone()
+    }
+}
+
+impl ToQuery for String {
+    /// Treats the string as a license number.
+    fn to_query(&self) -> Query {
+        Query::new().with_license_no(self.clone())
+    }
+}
+
+impl ToQuery for &str {
+    /// Treats the string as a license number.
+    fn to_query(&self) -> Query {
+        Query::new().with_license_no(self.to_string())
+    }
+}
+
+impl ToQuery for LicenseNumber {
+    fn to_query(&self) -> Query {
+        Query::new().with_license(self.clone())
+    }
+}
+
+/// Maps a role or sector, given in any form [`FromStr`] accepts, to the value the register's form expects.
+/// Values that don't parse are sent as they are.
+fn form_value<T: FromStr>(value: &str, form_value: fn(&T) -> &str) -> String {
+    value
+        .parse::<T>()
+        .map(|parsed| form_value(&parsed).to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::models::{LicenseRole, LicenseSector};
 
     use super::*;
 
+    #[test]
+    fn test_validate() {
+        let problems = |query: Query| match query.validate() {
+            Err(SIAError::InvalidQuery { problems }) => problems,
+            other => panic!("expected an invalid query, got {:?}", other),
+        };
+
+        assert_eq!(problems(Query::new()), vec![QueryProblem::Empty]);
+        assert!(Query::new()
+            .with_last_name("Smith".to_string())
+            .with_license_sector(LicenseSector::NoSector)
+            .validate()
+            .is_ok());
+        assert_eq!(
+            problems(Query::new().with_middle_name("James".to_string())),
+            vec![QueryProblem::MissingField(QueryField::LastName)]
+        );
+        assert_eq!(
+            problems(
+                Query::new()
+                    .with_license_no("123456".to_string())
+                    .with_last_name("Smith".to_string())
+            ),
+            vec![
+                QueryProblem::InvalidLicenseNumber(ParseLicenseNumberError::WrongLength(6)),
+                QueryProblem::Conflict(QueryField::LastName)
+            ]
+        );
+        assert_eq!(
+            problems(
+                Query::new()
+                    .with_last_name("Smith".to_string())
+                    .with_date_of_birth(NaiveDate::from_ymd_opt(70, 1, 1).unwrap())
+            ),
+            vec![QueryProblem::ImplausibleDateOfBirth(
+                NaiveDate::from_ymd_opt(70, 1, 1).unwrap()
+            )]
+        );
+        assert_eq!(
+            problems(
+                Query::new()
+                    .with_last_name(" ".to_string())
+                    .with_date_of_birth(NaiveDate::from_ymd_opt(2999, 1, 1).unwrap())
+            ),
+            vec![
+                QueryProblem::Blank(QueryField::LastName),
+                QueryProblem::FutureDateOfBirth(NaiveDate::from_ymd_opt(2999, 1, 1).unwrap())
+            ]
+        );
+
+        assert!(Query::new()
+            .with_license_no("1234 5678 9012 3456".to_string())
+            .validate()
+            .is_ok());
+        assert!(Query::new()
+            .with_last_name("Smith".to_string())
+            .with_role(LicenseRole::Frontline)
+            .with_license_sector(LicenseSector::DoorSupervision)
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_date_of_birth_is_formatted_for_the_form() {
+        let query = Query::new()
+            .with_last_name("Smith".to_string())
+            .with_date_of_birth(Query::parse_date_of_birth("1970-01-31").unwrap());
+
+        assert_eq!(query.to_search_by_name_payload().dob, "31/01/1970");
+        assert_eq!(Query::new().to_search_by_name_payload().dob, "");
+        assert_eq!(Query::parse_date_of_birth("31st Jan"), None);
+    }
+
+    #[test]
+    fn test_roles_and_sectors_use_form_values() {
+        let query = Query::new()
+            .with_last_name("Smith".to_string())
+            .with_role(LicenseRole::NonFrontline)
+            .with_license_sector(LicenseSector::NoSector);
+        assert!(query.validate().is_ok());
+
+        let payload = query.to_search_by_name_payload();
+        assert_eq!(payload.role, "Non Front Line");
+        assert_eq!(payload.license_sector, "");
+
+        let mut query = Query::new().with_last_name("Smith".to_string());
+        query.license_sector = Some(LicenseSector::NoSector.form_value().to_string());
+        assert!(query.validate().is_ok());
+        assert_eq!(query.to_search_by_name_payload().license_sector, "");
+
+        let mut query = Query::new().with_last_name("Smith".to_string());
+        query.license_sector = Some("door_supervision".to_string());
+        assert!(query.validate().is_ok());
+        assert_eq!(
+            query.to_search_by_name_payload().license_sector,
+            "Door Supervision"
+        );
+    }
+
     #[test_log::test]
     #[cfg(feature = "blocking")]
     fn test_query_search_with_name() {
@@ -143,7 +498,7 @@ mod tests {
             .with_last_name("Smith".to_string())
             .with_first_name("John".to_string())
             .with_middle_name("James".to_string())
-            .with_date_of_birth("01/01/1970".to_string())
+            .with_date_of_birth(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())
             .with_role(LicenseRole::Frontline)
             .with_license_sector(LicenseSector::DoorSupervision)
             .search_sync();
@@ -171,7 +526,8 @@ mod tests {
     fn test_query_search_with_license_no() {
         let query = Query::new().with_license_no("123456".to_string());
 
-        assert_eq!(query.license_no, Some("123456".to_string()));
+        assert_eq!(query.license_no, None);
+        assert_eq!(query.invalid_license_no, Some("123456".to_string()));
 
         let known_license_no = std::env::var("KNOWN_LICENSE_NO");
 
@@ -194,7 +550,7 @@ mod tests {
             .with_last_name("Smith".to_string())
             .with_first_name("John".to_string())
             .with_middle_name("James".to_string())
-            .with_date_of_birth("01/01/1970".to_string())
+            .with_date_of_birth(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap())
             .with_role(LicenseRole::Frontline)
             .with_license_sector(LicenseSector::DoorSupervision)
             .search()
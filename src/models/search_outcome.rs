@@ -0,0 +1,75 @@
+use crate::errors::SIAError;
+use crate::models::LicenseState;
+
+/// The result of a search that reached the register, separating a legitimate negative answer from failures.
+///
+/// Returned by [`crate::SiaClient::lookup`], where `Err` is reserved for searches that couldn't be completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchOutcome {
+    /// The register listed at least one license.
+    Found(Vec<LicenseState>),
+    /// The register has no license matching the search.
+    NotFound,
+    /// The search matched too many licenses for the register to list. Narrow it with a role,
+    /// sector or more of the name, or see [`crate::SiaClient::search_split`].
+    TooMany,
+}
+
+impl SearchOutcome {
+    /// Converts the result of a plain search, keeping only genuine failures as errors.
+    pub fn from_result(result: Result<Vec<LicenseState>, SIAError>) -> Result<Self, SIAError> {
+        match result {
+            Ok(licenses) if licenses.is_empty() => Ok(SearchOutcome::NotFound),
+            Ok(licenses) => Ok(SearchOutcome::Found(licenses)),
+            Err(SIAError::NoLicensesFound) => Ok(SearchOutcome::NotFound),
+            Err(SIAError::TooManyResults) => Ok(SearchOutcome::TooMany),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Checks if any licenses were found.
+    pub fn is_found(&self) -> bool {
+        matches!(self, SearchOutcome::Found(_))
+    }
+
+    /// Returns the licenses found, or an empty slice.
+    pub fn licenses(&self) -> &[LicenseState] {
+        match self {
+            SearchOutcome::Found(licenses) => licenses,
+            _ => &[],
+        }
+    }
+
+    /// Takes the licenses found, or an empty vector.
+    pub fn into_licenses(self) -> Vec<LicenseState> {
+        match self {
+            SearchOutcome::Found(licenses) => licenses,
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_result() {
+        assert_eq!(
+            SearchOutcome::from_result(Err(SIAError::NoLicensesFound)).unwrap(),
+            SearchOutcome::NotFound
+        );
+        assert_eq!(
+            SearchOutcome::from_result(Err(SIAError::TooManyResults)).unwrap(),
+            SearchOutcome::TooMany
+        );
+        assert_eq!(
+            SearchOutcome::from_result(Ok(Vec::new())).unwrap(),
+            SearchOutcome::NotFound
+        );
+        assert!(matches!(
+            SearchOutcome::from_result(Err(SIAError::ServiceUnavailable)),
+            Err(SIAError::ServiceUnavailable)
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+/// The limiter shared by every client that isn't given one explicitly.
+static SHARED: Lazy<RateLimiter> = Lazy::new(|| RateLimiter::new(2.0, 5));
+
+/// A token bucket limiting how often requests are sent to the register.
+///
+/// The bucket holds up to `burst` permits and refills at `requests_per_second`.
+/// Cloning a limiter shares its bucket, so one limiter can throttle many clients.
+///
+/// # Example
+///
+/// ```
+/// use sia_rs::{RateLimiter, SiaClient};
+///
+/// let limiter = RateLimiter::new(1.0, 3);
+/// let client = SiaClient::builder().rate_limiter(limiter.clone()).build().unwrap();
+///
+/// println!("Next permit in {:?}", limiter.time_until_next_permit());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Option<Arc<Mutex<Bucket>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated = now;
+    }
+
+    fn wait_for(&self, tokens: f64) -> Duration {
+        if tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - tokens) / self.rate)
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::shared()
+    }
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `requests_per_second` on average, with bursts of up to `burst` requests.
+    ///
+    /// A non-positive rate creates an unlimited limiter.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        if requests_per_second <= 0.0 || !requests_per_second.is_finite() {
+            return Self::unlimited();
+        }
+
+        let burst = f64::from(burst.max(1));
+        Self {
+            bucket: Some(Arc::new(Mutex::new(Bucket {
+                rate: requests_per_second,
+                burst,
+                tokens: burst,
+                updated: Instant::now(),
+            }))),
+        }
+    }
+
+    /// A limiter that never waits.
+    pub fn unlimited() -> Self {
+        Self { bucket: None }
+    }
+
+    /// The process-wide limiter used by default, allowing 2 requests per second with bursts of 5.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Returns how long a caller would currently have to wait for a permit.
+    pub fn time_until_next_permit(&self) -> Duration {
+        let Some(bucket) = &self.bucket else {
+            return Duration::ZERO;
+        };
+
+        let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill(Instant::now());
+        bucket.wait_for(bucket.tokens)
+    }
+
+    /// Takes a permit if one is available right now.
+    pub fn try_acquire(&self) -> bool {
+        let Some(bucket) = &self.bucket else {
+            return true;
+        };
+
+        let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill(Instant::now());
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reserves the next permit and returns how long to wait before using it.
+    fn reserve(&self) -> Duration {
+        let Some(bucket) = &self.bucket else {
+            return Duration::ZERO;
+        };
+
+        let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.refill(Instant::now());
+        let wait = bucket.wait_for(bucket.tokens);
+        bucket.tokens -= 1.0;
+        wait
+    }
+
+    /// Waits until a permit is available and takes it.
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            log::debug!("Rate limited, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Blocks the current thread until a permit is available and takes it.
+    pub fn acquire_blocking(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            log::debug!("Rate limited, waiting {:?}", wait);
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_wait() {
+        let limiter = RateLimiter::new(1.0, 2);
+
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        let wait = limiter.time_until_next_permit();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_reservations_queue_up() {
+        let limiter = RateLimiter::new(10.0, 1);
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert!(limiter.reserve() > Duration::from_millis(90));
+        assert!(limiter.reserve() > Duration::from_millis(190));
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = RateLimiter::unlimited();
+
+        for _ in 0..100 {
+            assert!(limiter.try_acquire());
+        }
+        assert_eq!(limiter.time_until_next_permit(), Duration::ZERO);
+    }
+}
@@ -1,59 +1,413 @@
 // blocking variant of the request functions
 
-use std::time::Duration;
-
-use log::{debug, error, warn};
-use reqwest::blocking::Client;
+use std::sync::{mpsc, Arc, Mutex};
 
+use crate::cache::CacheBackend;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::coverage::{Activity, Coverage};
 use crate::errors::SIAError;
 use crate::models::payloads::{SearchByLicense, SearchByName};
-use crate::models::LicenseState;
-use crate::requests::parsers::parse;
-use crate::{SEARCH_LICENSE_NUM_URL, SEARCH_NAME_URL};
+use crate::models::{LicenseNumber, LicenseState, Query, SearchOutcome, ToQuery};
+use crate::rate_limit::RateLimiter;
+use crate::requests::core::{Attempts, ClientCore, SearchRequest, Step};
+use crate::retry::RetryPolicy;
+use crate::single_flight::{BlockingSingleFlight, SearchResult};
+use crate::split::{self, SplitSearch};
+use crate::transport::{TransportError, TransportResponse};
+use crate::verify::{Candidate, Verification};
+use crate::SiaClientBuilder;
+
+/// The blocking counterpart of [`crate::Transport`]: a form POST goes in, a status and body come out.
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    /// Sends `params` as a url-encoded form to `url`.
+    fn post_form(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError>;
+}
+
+/// The default blocking transport, sending requests with a pooled [`reqwest::blocking::Client`].
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn post_form(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError> {
+        let res = self.client.post(url).form(params).send()?;
+
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    name.as_str().to_lowercase(),
+                    value.to_str().ok()?.to_string(),
+                ))
+            })
+            .collect();
+        let body = res.text()?;
+
+        Ok(TransportResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// A reusable blocking client for the SIA public register.
+///
+/// Holds a single transport, by default a pooled HTTP client, so connections are reused between searches.
+/// Build one with [`SiaClientBuilder::build_blocking`] to configure it.
+#[derive(Debug, Clone)]
+pub struct SiaClient {
+    core: ClientCore,
+    transport: Arc<dyn Transport>,
+    in_flight: Option<Arc<BlockingSingleFlight<SearchResult>>>,
+}
+
+impl Default for SiaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SiaClient {
+    /// Creates a client pointed at the public SIA register.
+    pub fn new() -> Self {
+        SiaClientBuilder::default()
+            .build_blocking()
+            .expect("the default client configuration is valid")
+    }
+
+    /// Creates a builder for configuring a client.
+    pub fn builder() -> SiaClientBuilder {
+        SiaClientBuilder::default()
+    }
+
+    pub(crate) fn from_builder(builder: SiaClientBuilder) -> Result<Self, SIAError> {
+        let transport: Arc<dyn Transport> = match builder.blocking_transport.clone() {
+            Some(transport) => transport,
+            None => {
+                let mut http = reqwest::blocking::Client::builder();
+                if let Some(timeout) = builder.timeout {
+                    http = http.timeout(timeout);
+                }
+                if let Some(user_agent) = &builder.user_agent {
+                    http = http.user_agent(user_agent);
+                }
+                let http = http
+                    .build()
+                    .map_err(|err| SIAError::ClientBuild { source: err.into() })?;
+                Arc::new(ReqwestTransport::new(http))
+            }
+        };
+
+        Ok(Self {
+            core: ClientCore::from_builder(&builder),
+            transport,
+            in_flight: builder
+                .deduplicate
+                .then(|| Arc::new(BlockingSingleFlight::new())),
+        })
+    }
+
+    /// The base URL this client sends requests to.
+    pub fn base_url(&self) -> &str {
+        &self.core.base_url
+    }
+
+    /// The policy used to retry failed requests.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.core.retry
+    }
+
+    /// The rate limiter every request from this client goes through.
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.core.rate_limiter
+    }
+
+    /// The circuit breaker guarding requests from this client, if one is set.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.core.circuit_breaker.as_ref()
+    }
+
+    /// The cache search results are stored in, if caching is enabled.
+    pub fn cache(&self) -> Option<&dyn CacheBackend> {
+        self.core.cache.as_deref()
+    }
+
+    /// Search for a license by either license number or name.
+    ///
+    /// The query is checked with [`Query::validate`] first, so an invalid query fails without contacting the register.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A query object that contains the search parameters.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<LicenseState>, SIAError>` - A vector of license states if the search was successful, otherwise an error.
+    pub fn search(&self, query: &Query) -> Result<Vec<LicenseState>, SIAError> {
+        match self.search_once(query) {
+            Err(SIAError::TooManyResults) if self.core.split_broad_searches => {
+                self.split(query)?.into_result()
+            }
+            result => result,
+        }
+    }
+
+    /// Searches like [`SiaClient::search`], but reports "no licenses found" and "too many results" as a
+    /// [`SearchOutcome`] rather than an error, so `Err` only means the search couldn't be completed.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A query object that contains the search parameters.
+    pub fn lookup(&self, query: &Query) -> Result<SearchOutcome, SIAError> {
+        SearchOutcome::from_result(self.search(query))
+    }
+
+    /// Searches, and if the query matches too many licenses, splits it into narrower searches by role
+    /// and sector and combines their results.
+    ///
+    /// Unlike [`SiaClient::search`] with [`SiaClientBuilder::split_broad_searches`], this returns whatever
+    /// was found even when some narrowed searches were still too broad, and lists those searches.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - A query object that contains the search parameters.
+    pub fn search_split(&self, query: &Query) -> Result<SplitSearch, SIAError> {
+        match self.search_once(query) {
+            Err(SIAError::TooManyResults) => self.split(query),
+            result => Ok(SplitSearch {
+                licenses: result?,
+                too_broad: Vec::new(),
+            }),
+        }
+    }
+
+    /// Searches without splitting broad queries.
+    fn search_once(&self, query: &Query) -> Result<Vec<LicenseState>, SIAError> {
+        self.execute(SearchRequest::from_query(query)?)
+    }
+
+    /// Runs a narrowed search for every role and sector the query leaves open, on a few worker threads.
+    fn split(&self, query: &Query) -> Result<SplitSearch, SIAError> {
+        let narrowed = split::sub_queries(query);
+        if narrowed.is_empty() {
+            return split::merge([(query.clone(), Err(SIAError::TooManyResults))]);
+        }
+
+        let queue = Mutex::new(narrowed.into_iter());
+        let results = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..split::SPLIT_CONCURRENCY {
+                scope.spawn(|| loop {
+                    let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                    let Some(query) = next else {
+                        break;
+                    };
+
+                    let result = self.search_once(&query);
+                    results
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push((query, result));
+                });
+            }
+        });
+
+        split::merge(results.into_inner().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Runs many searches on `concurrency` worker threads.
+    ///
+    /// Results are yielded in the order they complete, each paired with the input that produced it.
+    /// Every search goes through this client's rate limiter, retry policy and cache.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sia_rs::blocking::SiaClient;
+    ///
+    /// let client = SiaClient::new();
+    /// for (license_no, result) in client.search_many(vec!["1234567890123456", "6543210987654321"], 4) {
+    ///     println!("{}: {:?}", license_no, result);
+    /// }
+    /// ```
+    pub fn search_many<I>(
+        &self,
+        inputs: I,
+        concurrency: usize,
+    ) -> impl Iterator<Item = (I::Item, Result<Vec<LicenseState>, SIAError>)>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: ToQuery + Send + 'static,
+    {
+        let inputs = Arc::new(Mutex::new(inputs.into_iter()));
+        let (sender, receiver) = mpsc::channel();
+
+        for _ in 0..concurrency.max(1) {
+            let client = self.clone();
+            let inputs = inputs.clone();
+            let sender = sender.clone();
+
+            std::thread::spawn(move || loop {
+                let next = inputs.lock().unwrap_or_else(|e| e.into_inner()).next();
+                let Some(input) = next else {
+                    break;
+                };
+
+                let result = client.search(&input.to_query());
+                if sender.send((input, result)).is_err() {
+                    // The caller stopped iterating, so there is no point carrying on.
+                    break;
+                }
+            });
+        }
+
+        receiver.into_iter()
+    }
+
+    /// Looks up a candidate's license number and checks the license against the details they gave.
+    ///
+    /// A license number the register doesn't know gives [`crate::Verdict::NotFound`] rather than an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The license number and details to check.
+    pub fn verify(&self, candidate: &Candidate) -> Result<Verification, SIAError> {
+        let query = Query::new().with_license(candidate.license_number.clone());
+
+        match self.search(&query) {
+            Ok(licenses) => Ok(candidate.check(&licenses)),
+            Err(SIAError::NoLicensesFound) => Ok(candidate.check(&[])),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Looks up a license number and checks whether the license lets its holder carry out an activity today.
+    ///
+    /// A license number the register doesn't know gives [`crate::CoverageProblem::NotFound`] rather than an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `license_no` - The license to check.
+    /// * `activity` - The work the holder needs to be licensed for.
+    pub fn check_coverage(
+        &self,
+        license_no: &LicenseNumber,
+        activity: &Activity,
+    ) -> Result<Coverage, SIAError> {
+        let query = Query::new().with_license(license_no.clone());
+        let outcome = self.lookup(&query)?;
+
+        Ok(Coverage::check(outcome.licenses(), activity))
+    }
+
+    /// Search for a license by license number.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The search payload.
+    pub fn search_by_license(
+        &self,
+        payload: SearchByLicense,
+    ) -> Result<Vec<LicenseState>, SIAError> {
+        self.execute(SearchRequest::by_license(&payload))
+    }
+
+    /// Search for a license by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The search payload.
+    pub fn search_by_name(&self, payload: SearchByName) -> Result<Vec<LicenseState>, SIAError> {
+        self.execute(SearchRequest::by_name(&payload))
+    }
+
+    /// Sends a search, consulting the cache first if one is configured
+    /// and sharing the request with any identical search already in flight.
+    fn execute(&self, request: SearchRequest) -> Result<Vec<LicenseState>, SIAError> {
+        if let Some(cached) = self.core.cached(&request) {
+            return cached;
+        }
+
+        match &self.in_flight {
+            Some(in_flight) => in_flight.run(&request.key, || self.fetch(&request)),
+            None => self.fetch(&request),
+        }
+    }
+
+    /// Sends a search and stores the result in the cache.
+    fn fetch(&self, request: &SearchRequest) -> Result<Vec<LicenseState>, SIAError> {
+        let result = request_base(
+            self.transport.as_ref(),
+            &self.core.url(request),
+            &request.form(),
+            &self.core.retry,
+            &self.core.rate_limiter,
+            self.core.circuit_breaker.as_ref(),
+            self.core.strict_parsing,
+        );
+        self.core.store(request, &result);
+        result
+    }
+}
 
 /// Base function for making a request to the SIA website.
-/// Will retry the request with exponential backoff if it fails up to 3 times.
+/// Failed requests are retried according to the given retry policy.
 ///
 /// # Arguments
 ///
+/// * `transport` - The transport to send the request with.
 /// * `url` - The URL to make the request to.
 /// * `payload` - The request payload.
+/// * `retry` - The policy deciding which failures are retried and how long to wait between attempts.
+/// * `rate_limiter` - The limiter each attempt takes a permit from.
+/// * `circuit_breaker` - An optional breaker that each attempt reports to, failing fast while it is open.
+/// * `strict_parsing` - Whether to fail on any field that can't be parsed instead of filling in a default.
 ///
 /// # Returns
 ///
 /// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
-pub fn request_base(url: &str, payload: &Vec<(&str, &str)>) -> Result<Vec<LicenseState>, SIAError> {
-    let mut backoff = 1;
-    let client = Client::new();
+pub fn request_base(
+    transport: &dyn Transport,
+    url: &str,
+    payload: &[(&str, &str)],
+    retry: &RetryPolicy,
+    rate_limiter: &RateLimiter,
+    circuit_breaker: Option<&CircuitBreaker>,
+    strict_parsing: bool,
+) -> Result<Vec<LicenseState>, SIAError> {
+    let mut attempts = Attempts::new(retry, url)
+        .with_circuit_breaker(circuit_breaker)
+        .with_strict_parsing(strict_parsing);
 
     loop {
-        let res = client.post(url).form(payload).send();
-
-        match res {
-            Ok(res) => {
-                if res.status() == 200 {
-                    let body = res.text().unwrap();
-                    return parse(&body);
-                } else {
-                    error!("Request failed with status code: {}", res.status());
-                    if backoff > 8 {
-                        error!("Failed to make request after 3 attempts.");
-                        return Err(SIAError::RequestFailed(res.error_for_status().unwrap_err()));
-                    }
-                }
-            }
-            Err(err) => {
-                warn!("Error: {:?}", err);
-                if backoff > 8 {
-                    error!("Failed to make request after 3 attempts.");
-                    return Err(SIAError::RequestFailed(err));
-                }
-            }
-        }
+        attempts.begin()?;
+        rate_limiter.acquire_blocking();
+        let response = transport.post_form(url, payload);
 
-        let delay = Duration::from_secs(backoff);
-        std::thread::sleep(delay);
-        backoff *= 2;
+        match attempts.handle(response) {
+            Step::Done(result) => return result,
+            Step::Retry(delay) => std::thread::sleep(delay),
+        }
     }
 }
 
@@ -67,8 +421,7 @@ pub fn request_base(url: &str, payload: &Vec<(&str, &str)>) -> Result<Vec<Licens
 ///
 /// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
 pub fn request_search_by_license(payload: SearchByLicense) -> Result<Vec<LicenseState>, SIAError> {
-    let payload = payload.to_params();
-    request_base(SEARCH_LICENSE_NUM_URL, &payload)
+    SiaClient::new().search_by_license(payload)
 }
 
 /// Search for a license by name.
@@ -81,8 +434,7 @@ pub fn request_search_by_license(payload: SearchByLicense) -> Result<Vec<License
 ///
 /// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
 pub fn request_search_by_name(payload: SearchByName) -> Result<Vec<LicenseState>, SIAError> {
-    let payload = payload.to_params();
-    request_base(SEARCH_NAME_URL, &payload)
+    SiaClient::new().search_by_name(payload)
 }
 
 #[cfg(test)]
@@ -90,6 +442,63 @@ mod tests {
     use test_log;
 
     use super::*;
+    use crate::client::tests::serve;
+    use crate::{FixtureTransport, RateLimiter};
+
+    #[test_log::test]
+    fn test_broad_searches_are_split() {
+        let transport = FixtureTransport::new().with_default(TransportResponse::ok(include_str!(
+            "../../fixtures/no_results.html"
+        )));
+        transport.push_response(TransportResponse::ok(include_str!(
+            "../../fixtures/too_many_results.html"
+        )));
+        transport.push_response(TransportResponse::ok(include_str!(
+            "../../fixtures/license_found.html"
+        )));
+        let client = SiaClient::builder()
+            .blocking_transport(transport.clone())
+            .rate_limiter(RateLimiter::unlimited())
+            .build_blocking()
+            .unwrap();
+
+        let split = client
+            .search_split(&Query::new().with_last_name("Smith".to_string()))
+            .unwrap();
+
+        assert_eq!(split.licenses.len(), 1);
+        assert!(split.is_complete());
+        assert_eq!(transport.requests().len(), 15);
+    }
+
+    #[test_log::test]
+    fn test_search_many() {
+        let base_url = serve(vec![
+            (200, "<p>No results found</p>".to_string()),
+            (200, "<p>No results found</p>".to_string()),
+        ]);
+        let client = SiaClient::builder()
+            .base_url(base_url)
+            .rate_limiter(RateLimiter::unlimited())
+            .retry_policy(RetryPolicy::none())
+            .build_blocking()
+            .unwrap();
+
+        let results: Vec<_> = client
+            .search_many(
+                vec![
+                    "1111111111111111".to_string(),
+                    "2222222222222222".to_string(),
+                ],
+                2,
+            )
+            .collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|(_, result)| matches!(result, Err(SIAError::NoLicensesFound))));
+    }
 
     #[test_log::test]
     fn test_request_search_by_license_fail() {
@@ -0,0 +1,410 @@
+// sans-IO core shared by the async and blocking request functions
+//
+// Everything here is plain data in, plain data out: building requests from queries, consulting the cache,
+// and deciding what to do with each response. The drivers in `requests_async` and `blocking` only do the
+// sending and sleeping, so both behave identically.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::{debug, error, warn};
+
+use crate::cache::{CacheBackend, CacheKey, CachedResult};
+use crate::circuit_breaker::CircuitBreaker;
+use crate::client::SiaClientBuilder;
+use crate::errors::SIAError;
+use crate::models::payloads::{SearchByLicense, SearchByName};
+use crate::models::{LicenseState, Query};
+use crate::rate_limit::RateLimiter;
+use crate::requests::parsers::{classify_page, parse, parse_strict};
+use crate::retry::RetryPolicy;
+use crate::transport::{TransportError, TransportResponse};
+use crate::{SEARCH_LICENSE_NUM_PATH, SEARCH_NAME_PATH};
+
+/// A search ready to be sent: the endpoint, the form fields and the key identifying it.
+#[derive(Debug, Clone)]
+pub(crate) struct SearchRequest {
+    pub path: &'static str,
+    pub key: CacheKey,
+    pub params: Vec<(String, String)>,
+}
+
+impl SearchRequest {
+    /// Builds the request for a query, after checking it with [`Query::validate`].
+    pub fn from_query(query: &Query) -> Result<Self, SIAError> {
+        query.validate()?;
+
+        match query.license_number() {
+            Some(Ok(license_no)) => Ok(Self::by_license(&SearchByLicense {
+                license_no: license_no.into(),
+            })),
+            _ => Ok(Self::by_name(&query.to_search_by_name_payload())),
+        }
+    }
+
+    pub fn by_license(payload: &SearchByLicense) -> Self {
+        debug!("Searching for license number: {:?}", payload);
+        Self::new(
+            SEARCH_LICENSE_NUM_PATH,
+            CacheKey::from(payload),
+            payload.to_params(),
+        )
+    }
+
+    pub fn by_name(payload: &SearchByName) -> Self {
+        debug!("Searching for name: {:?}", payload);
+        Self::new(
+            SEARCH_NAME_PATH,
+            CacheKey::from(payload),
+            payload.to_params(),
+        )
+    }
+
+    fn new(path: &'static str, key: CacheKey, params: Vec<(&str, &str)>) -> Self {
+        Self {
+            path,
+            key,
+            params: params
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// The form fields in the shape transports expect.
+    pub fn form(&self) -> Vec<(&str, &str)> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+}
+
+/// Settings shared by the async and blocking clients.
+#[derive(Debug, Clone)]
+pub(crate) struct ClientCore {
+    pub base_url: String,
+    pub retry: RetryPolicy,
+    pub rate_limiter: RateLimiter,
+    pub circuit_breaker: Option<CircuitBreaker>,
+    pub strict_parsing: bool,
+    pub split_broad_searches: bool,
+    pub cache: Option<Arc<dyn CacheBackend>>,
+}
+
+impl ClientCore {
+    pub fn from_builder(builder: &SiaClientBuilder) -> Self {
+        Self {
+            base_url: builder.base_url.clone(),
+            retry: builder.retry.clone(),
+            rate_limiter: builder.rate_limiter.clone(),
+            circuit_breaker: builder.circuit_breaker.clone(),
+            strict_parsing: builder.strict_parsing,
+            split_broad_searches: builder.split_broad_searches,
+            cache: builder.cache.clone(),
+        }
+    }
+
+    /// The full URL a request is sent to.
+    pub fn url(&self, request: &SearchRequest) -> String {
+        format!("{}{}", self.base_url, request.path)
+    }
+
+    /// Returns the cached result for a request, if there is one.
+    pub fn cached(&self, request: &SearchRequest) -> Option<Result<Vec<LicenseState>, SIAError>> {
+        let cached = self.cache.as_ref()?.get(&request.key)?;
+        debug!("Serving {:?} from cache", request.key);
+        Some(cached.into_result())
+    }
+
+    /// Stores the result of a request in the cache, if it's worth keeping.
+    pub fn store(&self, request: &SearchRequest, result: &Result<Vec<LicenseState>, SIAError>) {
+        if let Some(cache) = &self.cache {
+            if let Some(cached) = CachedResult::from_result(result) {
+                cache.insert(request.key.clone(), cached);
+            }
+        }
+    }
+}
+
+/// What a driver should do after an attempt.
+#[derive(Debug)]
+pub(crate) enum Step {
+    /// Stop and return this result.
+    Done(Result<Vec<LicenseState>, SIAError>),
+    /// Wait this long, then try again.
+    Retry(Duration),
+}
+
+/// Tracks the attempts made for one request and decides, from each response, whether to retry.
+#[derive(Debug)]
+pub(crate) struct Attempts<'a> {
+    retry: &'a RetryPolicy,
+    url: &'a str,
+    circuit_breaker: Option<&'a CircuitBreaker>,
+    strict_parsing: bool,
+    attempt: u32,
+}
+
+impl<'a> Attempts<'a> {
+    pub fn new(retry: &'a RetryPolicy, url: &'a str) -> Self {
+        Self {
+            retry,
+            url,
+            circuit_breaker: None,
+            strict_parsing: false,
+            attempt: 0,
+        }
+    }
+
+    /// Reports the outcome of each attempt to a circuit breaker, and stops once it opens.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Option<&'a CircuitBreaker>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Fails on any field that can't be parsed, instead of filling in a default.
+    pub fn with_strict_parsing(mut self, strict_parsing: bool) -> Self {
+        self.strict_parsing = strict_parsing;
+        self
+    }
+
+    /// Checks if the next attempt may be sent. Fails fast while the circuit breaker is open.
+    pub fn begin(&self) -> Result<(), SIAError> {
+        match self.circuit_breaker {
+            Some(breaker) if !breaker.try_acquire() => {
+                warn!("Circuit breaker is open, not contacting the register");
+                Err(SIAError::ServiceUnavailable)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Classifies the outcome of an attempt.
+    pub fn handle(&mut self, response: Result<TransportResponse, TransportError>) -> Step {
+        self.attempt += 1;
+
+        if let Some(breaker) = self.circuit_breaker {
+            match &response {
+                Ok(res) if res.status < 500 => breaker.record_success(),
+                _ => breaker.record_failure(),
+            }
+        }
+
+        let (err, retryable, delay) = match response {
+            Ok(res) if res.is_success() && self.strict_parsing => {
+                return Step::Done(parse_strict(&res.body))
+            }
+            Ok(res) if res.is_success() => return Step::Done(parse(&res.body)),
+            Ok(res) => {
+                error!("Request failed with status code: {}", res.status);
+                let retry_after = res.header("retry-after").and_then(parse_retry_after);
+                let throttled = res.status == 429 || (res.status == 503 && retry_after.is_some());
+
+                if throttled {
+                    warn!(
+                        "Rate limited by the register, retry after {:?}",
+                        retry_after
+                    );
+                }
+
+                let err = if throttled {
+                    SIAError::RateLimited { retry_after }
+                } else {
+                    match classify_page(&res.body) {
+                        err @ (SIAError::Maintenance | SIAError::Blocked | SIAError::Captcha) => {
+                            err
+                        }
+                        _ => SIAError::UnexpectedStatus {
+                            status: res.status,
+                            url: self.url.to_string(),
+                            attempts: self.attempt,
+                        },
+                    }
+                };
+                (err, self.retry.is_retryable_status(res.status), retry_after)
+            }
+            Err(err) => {
+                warn!("Error: {:?}", err);
+                let retryable = self.retry.is_retryable_error(&err);
+                (
+                    SIAError::from_transport(err, self.url, self.attempt),
+                    retryable,
+                    None,
+                )
+            }
+        };
+
+        if !retryable {
+            return Step::Done(Err(err));
+        }
+
+        if !self.retry.has_attempts_left(self.attempt) {
+            error!("Failed to make request after {} attempts.", self.attempt);
+            return Step::Done(Err(err));
+        }
+
+        Step::Retry(match delay {
+            Some(delay) => delay.min(self.retry.max_retry_after),
+            None => self.retry.delay_for(self.attempt),
+        })
+    }
+}
+
+/// Parses a `Retry-After` header, given either as a number of seconds or as an HTTP date.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TransportErrorKind;
+
+    const URL: &str = "http://localhost/search";
+
+    #[test]
+    fn test_from_query_validates_and_normalises() {
+        let query = Query::new().with_license_no("1234 5678 9012 3456".to_string());
+        let request = SearchRequest::from_query(&query).unwrap();
+
+        assert_eq!(request.path, SEARCH_LICENSE_NUM_PATH);
+        assert_eq!(request.form(), vec![("LicenseNo", "1234567890123456")]);
+
+        let request =
+            SearchRequest::from_query(&Query::new().with_last_name("Smith".to_string())).unwrap();
+        assert_eq!(request.path, SEARCH_NAME_PATH);
+
+        for invalid in [
+            Query::new(),
+            Query::new().with_license_no("123456".to_string()),
+            query.with_last_name("Smith".to_string()),
+        ] {
+            assert!(matches!(
+                SearchRequest::from_query(&invalid),
+                Err(SIAError::InvalidQuery { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_attempts_classify_responses() {
+        let retry = RetryPolicy::new()
+            .with_max_attempts(2)
+            .with_jitter(false)
+            .with_base_delay(Duration::from_secs(1));
+        let mut attempts = Attempts::new(&retry, URL);
+
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::new(503, ""))),
+            Step::Retry(delay) if delay == Duration::from_secs(1)
+        ));
+        assert!(matches!(
+            attempts.handle(Err(TransportError::new(
+                TransportErrorKind::Timeout,
+                "timed out"
+            ))),
+            Step::Done(Err(SIAError::Timeout { attempts: 2, .. }))
+        ));
+
+        let mut attempts = Attempts::new(&retry, URL);
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::new(404, ""))),
+            Step::Done(Err(SIAError::UnexpectedStatus { status: 404, .. }))
+        ));
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::ok("<p>No results found</p>"))),
+            Step::Done(Err(SIAError::NoLicensesFound))
+        ));
+
+        let mut attempts = Attempts::new(&retry, URL);
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::new(
+                403,
+                include_str!("../../fixtures/blocked.html")
+            ))),
+            Step::Done(Err(SIAError::Blocked))
+        ));
+    }
+
+    #[test]
+    fn test_retry_after_is_honoured_and_capped() {
+        let retry = RetryPolicy::new()
+            .with_max_attempts(2)
+            .with_max_retry_after(Duration::from_secs(30));
+
+        let mut attempts = Attempts::new(&retry, URL);
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::new(429, "").with_header("Retry-After", "5"))),
+            Step::Retry(delay) if delay == Duration::from_secs(5)
+        ));
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::new(429, "").with_header("Retry-After", "5"))),
+            Step::Done(Err(SIAError::RateLimited { retry_after: Some(delay) })) if delay == Duration::from_secs(5)
+        ));
+
+        let mut attempts = Attempts::new(&retry, URL);
+        assert!(matches!(
+            attempts.handle(Ok(
+                TransportResponse::new(503, "").with_header("Retry-After", "120")
+            )),
+            Step::Retry(delay) if delay == Duration::from_secs(30)
+        ));
+        assert!(matches!(
+            attempts.handle(Ok(
+                TransportResponse::new(503, "").with_header("Retry-After", "120")
+            )),
+            Step::Done(Err(SIAError::RateLimited { retry_after: Some(delay) })) if delay == Duration::from_secs(120)
+        ));
+
+        let no_retry = RetryPolicy::none();
+        let mut attempts = Attempts::new(&no_retry, URL);
+        assert!(matches!(
+            attempts.handle(Ok(TransportResponse::new(503, ""))),
+            Step::Done(Err(SIAError::UnexpectedStatus {
+                status: 503,
+                attempts: 1,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_attempts_report_to_circuit_breaker() {
+        let retry = RetryPolicy::new().with_max_attempts(5).with_jitter(false);
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        let mut attempts = Attempts::new(&retry, URL).with_circuit_breaker(Some(&breaker));
+
+        for _ in 0..2 {
+            assert!(attempts.begin().is_ok());
+            let error = TransportError::new(TransportErrorKind::Connect, "refused");
+            assert!(matches!(attempts.handle(Err(error)), Step::Retry(_)));
+        }
+
+        assert!(matches!(
+            attempts.begin(),
+            Err(SIAError::ServiceUnavailable)
+        ));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 120 "), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
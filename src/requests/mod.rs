@@ -1,8 +1,7 @@
-pub use requests_async::{request_search_by_license, request_search_by_name};
-
+pub(crate) mod core;
 mod parse_selectors;
 mod parsers;
-mod requests_async;
+pub(crate) mod requests_async;
 
 #[cfg(feature = "blocking")]
 pub mod blocking;
@@ -1,6 +1,8 @@
 use once_cell::sync::Lazy;
 use scraper::Selector;
 
+use crate::models::LicenseField;
+
 pub static CONTAINER_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse("div[class*='well']").unwrap());
 pub static FIRST_NAME_SELECTOR: Lazy<Selector> =
@@ -24,3 +26,38 @@ pub static STATUS_REASON_SELECTOR: Lazy<Selector> = Lazy::new(|| {
 
 pub static LICENSE_CONDITIONS_SELECTOR: Lazy<Selector> =
     Lazy::new(|| Selector::parse("div:nth-of-type(5) > div > div:nth-of-type(2)").unwrap());
+
+/// The positional selector for each field, used when its label can't be found.
+pub fn positional_selector(field: LicenseField) -> &'static Selector {
+    match field {
+        LicenseField::FirstName => &FIRST_NAME_SELECTOR,
+        LicenseField::LastName => &LAST_NAME_SELECTOR,
+        LicenseField::LicenseNumber => &LICENSE_NUMBER_SELECTOR,
+        LicenseField::Role => &ROLE_SELECTOR,
+        LicenseField::Sector => &SECTOR_SELECTOR,
+        LicenseField::Expiry => &EXPIRY_SELECTOR,
+        LicenseField::Status => &STATUS_SELECTOR,
+        LicenseField::StatusReason => &STATUS_REASON_SELECTOR,
+        LicenseField::LicenseConditions => &LICENSE_CONDITIONS_SELECTOR,
+    }
+}
+
+/// The visible labels each field may appear under, lower-cased.
+pub fn field_labels(field: LicenseField) -> &'static [&'static str] {
+    match field {
+        LicenseField::FirstName => &["first name", "forename"],
+        LicenseField::LastName => &["surname", "last name"],
+        LicenseField::LicenseNumber => &["licence number", "license number", "licence no"],
+        LicenseField::Role => &["role", "licence role"],
+        LicenseField::Sector => &["licence sector", "license sector", "sector"],
+        LicenseField::Expiry => &["expiry date", "expiry", "expires"],
+        LicenseField::Status => &["status", "licence status"],
+        LicenseField::StatusReason => &["status explanation", "status reason"],
+        LicenseField::LicenseConditions => &[
+            "additional licence conditions",
+            "additional license conditions",
+            "licence conditions",
+            "conditions",
+        ],
+    }
+}
@@ -1,14 +1,12 @@
-use chrono::NaiveDate;
 use log::{debug, warn};
 use scraper::ElementRef;
 
 use crate::errors::SIAError;
-use crate::models::{LicenseRole, LicenseSector, LicenseState};
-use crate::requests::parse_selectors::{
-    CONTAINER_SELECTOR, EXPIRY_SELECTOR, FIRST_NAME_SELECTOR, LAST_NAME_SELECTOR,
-    LICENSE_CONDITIONS_SELECTOR, LICENSE_NUMBER_SELECTOR, ROLE_SELECTOR, SECTOR_SELECTOR,
-    STATUS_REASON_SELECTOR, STATUS_SELECTOR,
+use crate::models::{
+    parse_date, LicenseField, LicenseNumber, LicenseRole, LicenseSector, LicenseState,
+    LicenseStatus, ParseReport, ParseStrategy,
 };
+use crate::requests::parse_selectors::{field_labels, positional_selector, CONTAINER_SELECTOR};
 
 pub fn select_first(selector: &scraper::Selector, fragment: &scraper::Html) -> Option<String> {
     fragment
@@ -32,22 +30,160 @@ pub fn string_post_process(input: &str) -> String {
     output
 }
 
-fn logged_unwrap_or<T: Default>(input: Option<T>, message: &str) -> T {
-    match input {
-        Some(value) => value,
+/// Collapses whitespace and lower-cases text so it can be compared with a label.
+fn normalise_label(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(':')
+        .trim_end()
+        .to_lowercase()
+}
+
+/// Finds the value shown next to one of a field's labels.
+///
+/// The label can be any element whose whole text matches, and the value is the next element after it.
+pub fn select_by_label(container: &ElementRef, labels: &[&str]) -> Option<String> {
+    container
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|element| {
+            labels.contains(&normalise_label(&element.text().collect::<String>()).as_str())
+        })
+        .find_map(|label| label.next_siblings().find_map(ElementRef::wrap))
+        .map(|value| value.text().collect::<String>())
+}
+
+/// Finds a field by its label, falling back to its position in the markup, and records which worked.
+fn find_field(
+    container: &ElementRef,
+    fragment: &scraper::Html,
+    field: LicenseField,
+    report: &mut ParseReport,
+) -> Option<String> {
+    let (value, strategy) = match select_by_label(container, field_labels(field)) {
+        Some(value) => (value, ParseStrategy::Label),
+        None => (
+            select_first(positional_selector(field), fragment)?,
+            ParseStrategy::Positional,
+        ),
+    };
+
+    report.strategies.insert(field, strategy);
+    Some(value)
+}
+
+/// Post processes a field's value, or records it as missing and substitutes an empty string.
+fn take(value: Option<String>, field: LicenseField, report: &mut ParseReport) -> String {
+    match value {
+        Some(value) => string_post_process(&value),
         None => {
-            warn!("{} - please report this issue", message);
-            Default::default()
+            warn!("Unable to find {} - please report this issue", field);
+            report.record_missing(field);
+            report.defaulted.push(field);
+            String::new()
         }
     }
 }
 
+/// Phrases that only appear on the register's downtime pages.
+const MAINTENANCE_MARKERS: [&str; 5] = [
+    "scheduled maintenance",
+    "down for maintenance",
+    "service is unavailable",
+    "service unavailable",
+    "temporarily unavailable",
+];
+
+/// Phrases used by firewalls and bot protection when refusing a request.
+const BLOCKED_MARKERS: [&str; 6] = [
+    "the requested url was rejected",
+    "request rejected",
+    "access denied",
+    "you have been blocked",
+    "attention required",
+    "incapsula incident",
+];
+
+/// Markers of a challenge page asking the visitor to prove they are human.
+const CAPTCHA_MARKERS: [&str; 5] = [
+    "captcha",
+    "verify you are a human",
+    "verify you are human",
+    "are you a robot",
+    "cf-challenge",
+];
+
+/// The longest snippet of an unrecognised page kept in an error.
+const SNIPPET_LENGTH: usize = 200;
+
+/// Works out what a page without any licence containers is, so a problem with the site
+/// is never mistaken for a search that found nothing.
+///
+/// # Arguments
+///
+/// * `html_body` - The HTML body of the page
+///
+/// # Returns
+///
+/// * `SIAError` - The error describing the page.
+pub fn classify_page(html_body: &str) -> SIAError {
+    let lowered = html_body.to_lowercase();
+    let contains_any = |markers: &[&str]| markers.iter().any(|marker| lowered.contains(marker));
+
+    if contains_any(&CAPTCHA_MARKERS) {
+        SIAError::Captcha
+    } else if contains_any(&BLOCKED_MARKERS) {
+        SIAError::Blocked
+    } else if contains_any(&MAINTENANCE_MARKERS) {
+        SIAError::Maintenance
+    } else {
+        SIAError::UnrecognisedPage {
+            snippet: snippet(html_body),
+        }
+    }
+}
+
+/// The start of a page's visible text, with whitespace collapsed.
+pub fn snippet(html_body: &str) -> String {
+    let document = scraper::Html::parse_document(html_body);
+    let text = document
+        .root_element()
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    match text.char_indices().nth(SNIPPET_LENGTH) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
 /// Parse the HTML body of the search results page
 ///
+/// Fields that can't be found or understood are filled with defaults and listed in each license's
+/// [`ParseReport`].
+///
 /// # Arguments
 ///
 /// * `html_body` - The HTML body of the search results page
 pub fn parse(html_body: &str) -> Result<Vec<LicenseState>, SIAError> {
+    parse_page(html_body, false)
+}
+
+/// Parse the HTML body of the search results page, failing instead of guessing.
+///
+/// Any field that would be missing, defaulted or unrecognised is returned as [`SIAError::ParseFailed`].
+///
+/// # Arguments
+///
+/// * `html_body` - The HTML body of the search results page
+pub fn parse_strict(html_body: &str) -> Result<Vec<LicenseState>, SIAError> {
+    parse_page(html_body, true)
+}
+
+fn parse_page(html_body: &str, strict: bool) -> Result<Vec<LicenseState>, SIAError> {
     if html_body.contains("No results found") {
         return Err(SIAError::NoLicensesFound);
     }
@@ -60,7 +196,8 @@ pub fn parse(html_body: &str) -> Result<Vec<LicenseState>, SIAError> {
     let containers: Vec<ElementRef> = document.select(&CONTAINER_SELECTOR).collect();
 
     if containers.is_empty() {
-        return Err(SIAError::NoLicensesFound);
+        warn!("Unable to find any license containers, classifying page");
+        return Err(classify_page(html_body));
     }
 
     debug!("Found {} license containers", containers.len());
@@ -68,69 +205,117 @@ pub fn parse(html_body: &str) -> Result<Vec<LicenseState>, SIAError> {
 
     for container in containers {
         let fragment = scraper::Html::parse_fragment(&container.inner_html());
+        let mut report = ParseReport::default();
+        let mut field = |field| find_field(&container, &fragment, field, &mut report);
 
-        let first_name = select_first(&FIRST_NAME_SELECTOR, &fragment);
-        let last_name = select_first(&LAST_NAME_SELECTOR, &fragment);
-        let license_number = select_first(&LICENSE_NUMBER_SELECTOR, &fragment);
-        let role = select_first(&ROLE_SELECTOR, &fragment);
-        let sector = select_first(&SECTOR_SELECTOR, &fragment);
-        let expiry_raw = select_first(&EXPIRY_SELECTOR, &fragment);
-        let status = select_first(&STATUS_SELECTOR, &fragment);
-        let status_reason = select_first(&STATUS_REASON_SELECTOR, &fragment);
-        let license_conditions = select_first(&LICENSE_CONDITIONS_SELECTOR, &fragment);
-
-        let expiry: NaiveDate = if expiry_raw.is_some() {
-            let expiry_t = logged_unwrap_or(expiry_raw, "Unable to find expiry date");
-            let expiry_t = string_post_process(&expiry_t);
-            NaiveDate::parse_from_str(&expiry_t, "%d %B %Y").unwrap_or_default()
-        } else {
-            NaiveDate::default()
-        };
+        let first_name = field(LicenseField::FirstName);
+        let last_name = field(LicenseField::LastName);
+        let license_number = field(LicenseField::LicenseNumber);
+        let role = field(LicenseField::Role);
+        let sector = field(LicenseField::Sector);
+        let expiry = field(LicenseField::Expiry);
+        let status = field(LicenseField::Status);
+        let status_reason = field(LicenseField::StatusReason);
+        let license_conditions = field(LicenseField::LicenseConditions);
 
-        if first_name.is_none()
-            && last_name.is_none()
-            && license_number.is_none()
-            && role.is_none()
-            && sector.is_none()
-            && status.is_none()
-            && status_reason.is_none()
-            && license_conditions.is_none()
-        {
+        if report.strategies.is_empty() {
             warn!("Unable to parse license - please report this issue. Aborting.");
-            return Err(SIAError::ParseFailed);
+            return Err(SIAError::ParseFailed {
+                field: None,
+                snippet: snippet(&container.html()),
+            });
+        }
+
+        if report.used_fallback() {
+            warn!(
+                "Some fields were found by position rather than label - please report this issue: {:?}",
+                report.strategies
+            );
+        }
+
+        let first_name = take(first_name, LicenseField::FirstName, &mut report);
+        let last_name = take(last_name, LicenseField::LastName, &mut report);
+        let raw_license_number = take(license_number, LicenseField::LicenseNumber, &mut report);
+        let license_number = match LicenseNumber::new(&raw_license_number) {
+            Ok(license_no) => Some(license_no),
+            Err(err) => {
+                if !raw_license_number.is_empty() {
+                    warn!("Invalid license number: {} ({})", raw_license_number, err);
+                    report.record_unknown(LicenseField::LicenseNumber, &raw_license_number);
+                }
+                None
+            }
+        };
+
+        let role_raw = take(role, LicenseField::Role, &mut report);
+        let role = LicenseRole::from(&role_raw);
+        if role.is_unknown() && !role_raw.is_empty() {
+            report.record_unknown(LicenseField::Role, &role_raw);
+        }
+
+        let sector_raw = take(sector, LicenseField::Sector, &mut report);
+        let sector = LicenseSector::from(&sector_raw);
+        if sector.is_unknown() && !sector_raw.is_empty() {
+            report.record_unknown(LicenseField::Sector, &sector_raw);
+        }
+
+        let expiry = match expiry.map(|expiry| string_post_process(&expiry)) {
+            Some(expiry_raw) => {
+                let expiry = parse_date(&expiry_raw);
+                if expiry.is_none() {
+                    warn!(
+                        "Unable to parse expiry date: {} - please report this issue",
+                        expiry_raw
+                    );
+                    report.record_unknown(LicenseField::Expiry, &expiry_raw);
+                }
+                expiry
+            }
+            None => {
+                warn!("Unable to find expiry date - please report this issue");
+                report.record_missing(LicenseField::Expiry);
+                None
+            }
+        };
+
+        let status_raw = take(status, LicenseField::Status, &mut report);
+        let status = LicenseStatus::from(&status_raw);
+        if status.is_unknown() && !status_raw.is_empty() {
+            report.record_unknown(LicenseField::Status, &status_raw);
+        }
+
+        let status_reason = take(status_reason, LicenseField::StatusReason, &mut report);
+        let license_conditions = take(
+            license_conditions,
+            LicenseField::LicenseConditions,
+            &mut report,
+        );
+
+        if strict {
+            if let Some(field) = report.first_gap() {
+                let snippet = match report.unknown_values.get(&field) {
+                    Some(value) => value.clone(),
+                    None => snippet(&container.html()),
+                };
+                return Err(SIAError::ParseFailed {
+                    field: Some(field),
+                    snippet,
+                });
+            }
         }
 
         let license = LicenseState {
-            first_name: string_post_process(&logged_unwrap_or(
-                first_name,
-                "Unable to find first name",
-            )),
-            last_name: string_post_process(&logged_unwrap_or(
-                last_name,
-                "Unable to find last name",
-            )),
-            license_number: string_post_process(&logged_unwrap_or(
-                license_number,
-                "Unable to find license number",
-            )),
-            role: LicenseRole::from(&string_post_process(&logged_unwrap_or(
-                role,
-                "Unable to find role",
-            ))),
-            sector: LicenseSector::from(&string_post_process(&logged_unwrap_or(
-                sector,
-                "Unable to find sector",
-            ))),
+            first_name,
+            last_name,
+            license_number,
+            raw_license_number,
+            role,
+            sector,
             expiry,
-            status: string_post_process(&logged_unwrap_or(status, "Unable to find status")),
-            status_reason: string_post_process(&logged_unwrap_or(
-                status_reason,
-                "Unable to find status reason",
-            )),
-            license_conditions: string_post_process(&logged_unwrap_or(
-                license_conditions,
-                "Unable to find license conditions",
-            )),
+            status,
+            status_reason,
+            license_conditions,
+            parse_report: report,
         };
 
         debug!("Parsed license: {:?}", license.license_number);
@@ -140,3 +325,171 @@ pub fn parse(html_body: &str) -> Result<Vec<LicenseState>, SIAError> {
 
     Ok(licenses)
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn test_known_pages_are_classified() {
+        assert!(matches!(
+            parse(include_str!("../../fixtures/no_results.html")),
+            Err(SIAError::NoLicensesFound)
+        ));
+        assert!(matches!(
+            parse(include_str!("../../fixtures/too_many_results.html")),
+            Err(SIAError::TooManyResults)
+        ));
+        assert!(matches!(
+            parse(include_str!("../../fixtures/maintenance.html")),
+            Err(SIAError::Maintenance)
+        ));
+        assert!(matches!(
+            parse(include_str!("../../fixtures/blocked.html")),
+            Err(SIAError::Blocked)
+        ));
+        assert!(matches!(
+            parse(include_str!("../../fixtures/captcha.html")),
+            Err(SIAError::Captcha)
+        ));
+    }
+
+    #[test]
+    fn test_unrecognised_page_keeps_a_snippet() {
+        let Err(SIAError::UnrecognisedPage { snippet }) =
+            parse(include_str!("../../fixtures/unrecognised.html"))
+        else {
+            panic!("expected an unrecognised page");
+        };
+
+        assert!(snippet.starts_with("Search the register"));
+        assert!(snippet.contains("John Smith"));
+        assert!(!snippet.contains('<'));
+    }
+
+    #[test]
+    fn test_fields_are_found_by_label() {
+        for page in [
+            include_str!("../../fixtures/license_found.html"),
+            include_str!("../../fixtures/license_reordered.html"),
+        ] {
+            let licenses = parse(page).unwrap();
+            let license = &licenses[0];
+
+            assert_eq!(license.first_name, "JOHN");
+            assert_eq!(license.last_name, "SMITH");
+            assert_eq!(license.license_number_str(), "1234567890123456");
+            assert_eq!(license.role, LicenseRole::Frontline);
+            assert_eq!(license.sector, LicenseSector::DoorSupervision);
+            assert_eq!(license.expiry, NaiveDate::from_ymd_opt(2030, 1, 1));
+            assert_eq!(license.status, LicenseStatus::Active);
+            assert_eq!(license.status_reason, "This licence is valid.");
+            assert_eq!(license.license_conditions, "None");
+
+            for field in LicenseField::ALL {
+                assert_eq!(
+                    license.parse_report.strategy(field),
+                    Some(ParseStrategy::Label),
+                    "{}",
+                    field
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_positional_fallback_is_recorded() {
+        let page = include_str!("../../fixtures/license_found.html")
+            .replace(">First name<", "><")
+            .replace(">Licence number<", "><");
+        let licenses = parse(&page).unwrap();
+        let report = &licenses[0].parse_report;
+
+        assert_eq!(licenses[0].first_name, "JOHN");
+        assert_eq!(licenses[0].license_number_str(), "1234567890123456");
+        assert_eq!(
+            report.strategy(LicenseField::FirstName),
+            Some(ParseStrategy::Positional)
+        );
+        assert_eq!(
+            report.strategy(LicenseField::LicenseNumber),
+            Some(ParseStrategy::Positional)
+        );
+        assert_eq!(
+            report.strategy(LicenseField::Role),
+            Some(ParseStrategy::Label)
+        );
+        assert!(report.used_fallback());
+    }
+
+    #[test]
+    fn test_invalid_license_number_keeps_the_raw_text() {
+        let page = include_str!("../../fixtures/license_found.html")
+            .replace(">1234567890123456<", ">1234-REDACTED<");
+        let licenses = parse(&page).unwrap();
+
+        assert_eq!(licenses[0].license_number, None);
+        assert_eq!(licenses[0].raw_license_number, "1234-REDACTED");
+        assert_eq!(licenses[0].license_number_str(), "1234-REDACTED");
+        assert_eq!(
+            licenses[0].parse_report.unknown_values[&LicenseField::LicenseNumber],
+            "1234-REDACTED"
+        );
+    }
+
+    #[test]
+    fn test_two_digit_expiry_year_is_unknown() {
+        let page = include_str!("../../fixtures/license_found.html")
+            .replace("01 January 2030", "01/01/30");
+        let licenses = parse(&page).unwrap();
+
+        assert_eq!(licenses[0].expiry, None);
+        assert_eq!(
+            licenses[0].parse_report.unknown_values[&LicenseField::Expiry],
+            "01/01/30"
+        );
+    }
+
+    #[test]
+    fn test_gaps_are_reported() {
+        let complete = parse(include_str!("../../fixtures/license_found.html")).unwrap();
+        assert!(complete[0].parse_report.is_complete());
+        assert!(parse_strict(include_str!("../../fixtures/license_found.html")).is_ok());
+
+        let page = include_str!("../../fixtures/license_found.html")
+            .replace("Front Line", "Trainee")
+            .replace("01 January 2030", "soon")
+            .replace(">Active<", ">Pending<")
+            .replace(
+                r#"<div class="ax_paragraph">Additional licence conditions</div>
+                <div class="ax_h4">None</div>"#,
+                "",
+            );
+        let licenses = parse(&page).unwrap();
+        let report = &licenses[0].parse_report;
+
+        assert_eq!(
+            licenses[0].role,
+            LicenseRole::Unknown("Trainee".to_string())
+        );
+        assert_eq!(
+            licenses[0].status,
+            LicenseStatus::Unknown("Pending".to_string())
+        );
+        assert_eq!(licenses[0].expiry, None);
+        assert_eq!(licenses[0].remaining_days(), None);
+        assert_eq!(report.missing, vec![LicenseField::LicenseConditions]);
+        assert_eq!(report.defaulted, vec![LicenseField::LicenseConditions]);
+        assert_eq!(report.unknown_values[&LicenseField::Role], "Trainee");
+        assert_eq!(report.unknown_values[&LicenseField::Expiry], "soon");
+        assert_eq!(report.unknown_values[&LicenseField::Status], "Pending");
+        assert!(!report.is_complete());
+
+        assert!(matches!(
+            parse_strict(&page),
+            Err(SIAError::ParseFailed { field: Some(LicenseField::Role), snippet }) if snippet == "Trainee"
+        ));
+    }
+}
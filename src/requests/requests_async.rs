@@ -1,98 +1,58 @@
 // async variant of the request functions
 
-use std::time::Duration;
-
-use log::{error, warn};
-use reqwest::Client;
-
+use crate::circuit_breaker::CircuitBreaker;
 use crate::errors::SIAError;
-use crate::models::payloads::{SearchByLicense, SearchByName};
 use crate::models::LicenseState;
-use crate::requests::parsers::parse;
-use crate::{SEARCH_LICENSE_NUM_URL, SEARCH_NAME_URL};
+use crate::rate_limit::RateLimiter;
+use crate::requests::core::{Attempts, Step};
+use crate::retry::RetryPolicy;
+use crate::transport::Transport;
 
 /// Base function for making a request to the SIA website.
-/// Will retry the request with exponential backoff if it fails up to 3 times.
+/// Failed requests are retried according to the given retry policy.
 ///
 /// # Arguments
 ///
+/// * `transport` - The transport to send the request with.
 /// * `url` - The URL to make the request to.
 /// * `payload` - The request payload.
+/// * `retry` - The policy deciding which failures are retried and how long to wait between attempts.
+/// * `rate_limiter` - The limiter each attempt takes a permit from.
+/// * `circuit_breaker` - An optional breaker that each attempt reports to, failing fast while it is open.
+/// * `strict_parsing` - Whether to fail on any field that can't be parsed instead of filling in a default.
 ///
 /// # Returns
 ///
 /// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
-async fn request_base(
+pub(crate) async fn request_base(
+    transport: &dyn Transport,
     url: &str,
-    payload: &Vec<(&str, &str)>,
+    payload: &[(&str, &str)],
+    retry: &RetryPolicy,
+    rate_limiter: &RateLimiter,
+    circuit_breaker: Option<&CircuitBreaker>,
+    strict_parsing: bool,
 ) -> Result<Vec<LicenseState>, SIAError> {
-    let mut backoff = 1;
-    let client = Client::new();
+    let mut attempts = Attempts::new(retry, url)
+        .with_circuit_breaker(circuit_breaker)
+        .with_strict_parsing(strict_parsing);
 
     loop {
-        let res = client.post(url).form(payload).send().await;
-
-        match res {
-            Ok(res) => {
-                if res.status() == 200 {
-                    let body = res.text().await.unwrap();
-                    return parse(&body);
-                } else {
-                    error!("Request failed with status code: {}", res.status());
-                    if backoff > 8 {
-                        error!("Failed to make request after 3 attempts.");
-                        return Err(SIAError::RequestFailed(res.error_for_status().unwrap_err()));
-                    }
-                }
-            }
-            Err(err) => {
-                warn!("Error: {:?}", err);
-                if backoff > 8 {
-                    error!("Failed to make request after 3 attempts.");
-                    return Err(SIAError::RequestFailed(err));
-                }
-            }
-        }
+        attempts.begin()?;
+        rate_limiter.acquire().await;
+        let response = transport.post_form(url, payload).await;
 
-        let delay = Duration::from_secs(backoff);
-        tokio::time::sleep(delay).await;
-        backoff *= 2;
+        match attempts.handle(response) {
+            Step::Done(result) => return result,
+            Step::Retry(delay) => tokio::time::sleep(delay).await,
+        }
     }
 }
 
-/// Search for a license by license number
-///
-/// # Arguments
-///
-/// * `payload` - A SearchByLicense object that contains the search parameters.
-///
-/// # Returns
-///
-/// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
-pub async fn request_search_by_license(
-    payload: SearchByLicense,
-) -> Result<Vec<LicenseState>, SIAError> {
-    log::debug!("Searching for license number: {:?}", payload);
-    request_base(SEARCH_LICENSE_NUM_URL, &payload.to_params()).await
-}
-
-/// Search for a license by name
-///
-/// # Arguments
-///
-/// * `payload` - A SearchByName object that contains the search parameters.
-///
-/// # Returns
-///
-/// * `Result<Vec<LicenseState>, RequestError>` - A vector of license states if the search was successful, otherwise an error.
-pub async fn request_search_by_name(payload: SearchByName) -> Result<Vec<LicenseState>, SIAError> {
-    log::debug!("Searching for name: {:?}", payload);
-    request_base(SEARCH_NAME_URL, &payload.to_params()).await
-}
-
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::models::payloads::{SearchByLicense, SearchByName};
+    use crate::SiaClient;
 
     #[test_log::test(tokio::test)]
     async fn test_request_search_by_license_fail() {
@@ -100,7 +60,7 @@ mod tests {
             license_no: "123456".to_string(),
         };
 
-        let result = request_search_by_license(payload).await;
+        let result = SiaClient::new().search_by_license(payload).await;
         assert!(result.is_err());
     }
 
@@ -117,7 +77,7 @@ mod tests {
             license_no: known_license_no.unwrap(),
         };
 
-        let result = request_search_by_license(payload).await;
+        let result = SiaClient::new().search_by_license(payload).await;
         assert!(result.is_err());
     }
 
@@ -137,7 +97,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = request_search_by_name(payload).await;
+        let result = SiaClient::new().search_by_name(payload).await;
         assert!(result.is_ok());
 
         assert_eq!(result.unwrap().len(), 2);
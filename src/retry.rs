@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use crate::transport::{TransportError, TransportErrorKind};
+
+/// Controls how failed requests to the register are retried.
+/// Follows the builder pattern.
+///
+/// Delays grow exponentially from `base_delay`, doubling after each attempt, and are capped at `max_delay`.
+/// With jitter enabled each delay is randomised to between half and all of its nominal value.
+/// When the register sends a `Retry-After` header, that delay is used instead, capped at `max_retry_after`.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use sia_rs::RetryPolicy;
+///
+/// let policy = RetryPolicy::new()
+///     .with_max_attempts(3)
+///     .with_base_delay(Duration::from_millis(200))
+///     .with_max_delay(Duration::from_secs(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first request. Always at least 1.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub base_delay: Duration,
+    /// The upper bound for any single delay.
+    pub max_delay: Duration,
+    /// Whether to randomise delays to avoid many clients retrying in lockstep.
+    pub jitter: bool,
+    /// HTTP status codes that are worth retrying. Any other non-success status fails immediately.
+    pub retryable_statuses: Vec<u16>,
+    /// Whether to retry when the connection could not be established.
+    pub retry_connect_errors: bool,
+    /// Whether to retry when the request timed out.
+    pub retry_timeouts: bool,
+    /// The longest the client will wait between attempts when throttled. A longer `Retry-After` is cut
+    /// down to this, and the request is retried as usual.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(8),
+            jitter: true,
+            retryable_statuses: vec![408, 429, 500, 502, 503, 504],
+            retry_connect_errors: true,
+            retry_timeouts: true,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts, including the first request.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Sets the upper bound for any single delay.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Enables or disables jitter.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the HTTP status codes that are worth retrying.
+    pub fn with_retryable_statuses(mut self, statuses: Vec<u16>) -> Self {
+        self.retryable_statuses = statuses;
+        self
+    }
+
+    /// Sets whether connection failures are retried.
+    pub fn with_retry_connect_errors(mut self, retry: bool) -> Self {
+        self.retry_connect_errors = retry;
+        self
+    }
+
+    /// Sets whether timeouts are retried.
+    pub fn with_retry_timeouts(mut self, retry: bool) -> Self {
+        self.retry_timeouts = retry;
+        self
+    }
+
+    /// Sets the longest the client will wait for a `Retry-After` when throttled.
+    pub fn with_max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Checks if a response with the given status code should be retried.
+    pub fn is_retryable_status(&self, status: u16) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    /// Checks if another attempt is allowed after `attempt` attempts have been made.
+    pub fn has_attempts_left(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Returns the delay to wait after the given attempt (starting at 1) has failed.
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        if self.jitter && !delay.is_zero() {
+            let half = delay / 2;
+            half + delay.mul_f64(fastrand::f64()) / 2
+        } else {
+            delay
+        }
+    }
+
+    /// Checks if a transport error should be retried.
+    pub fn is_retryable_error(&self, err: &TransportError) -> bool {
+        match err.kind {
+            TransportErrorKind::Connect => self.retry_connect_errors,
+            TransportErrorKind::Timeout => self.retry_timeouts,
+            TransportErrorKind::Body | TransportErrorKind::Other => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_and_caps() {
+        let policy = RetryPolicy::new()
+            .with_jitter(false)
+            .with_base_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(5));
+
+        assert_eq!(policy.delay_for(1), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(2), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(3), Duration::from_secs(4));
+        assert_eq!(policy.delay_for(4), Duration::from_secs(5));
+        assert_eq!(policy.delay_for(100), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_range() {
+        let policy = RetryPolicy::new().with_base_delay(Duration::from_secs(2));
+
+        for _ in 0..100 {
+            let delay = policy.delay_for(1);
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        let policy = RetryPolicy::new();
+
+        assert!(!policy.is_retryable_status(404));
+        assert!(!policy.is_retryable_status(400));
+        assert!(policy.is_retryable_status(503));
+        assert!(!RetryPolicy::none().has_attempts_left(1));
+    }
+}
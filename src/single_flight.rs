@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use tokio::sync::watch;
+
+use crate::cache::CacheKey;
+use crate::models::LicenseState;
+use crate::SIAError;
+
+/// The result shared between callers of an identical search.
+pub(crate) type SearchResult = Result<Vec<LicenseState>, SIAError>;
+
+/// Deduplicates concurrent calls that share a key, so only the first caller does the work
+/// and everyone waiting on it receives a clone of its result.
+///
+/// If the caller doing the work is cancelled, one of the waiters takes over.
+#[derive(Debug)]
+pub(crate) struct SingleFlight<T> {
+    calls: Mutex<HashMap<CacheKey, watch::Receiver<Option<T>>>>,
+}
+
+/// Removes a call from the map when its leader finishes or is dropped.
+struct Leader<'a, T> {
+    flight: &'a SingleFlight<T>,
+    key: &'a CacheKey,
+}
+
+impl<T> Drop for Leader<'_, T> {
+    fn drop(&mut self) {
+        self.flight
+            .calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(self.key);
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `work` unless a call with the same key is already in flight, in which case its result is shared.
+    pub(crate) async fn run<F, Fut>(&self, key: &CacheKey, work: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        loop {
+            let (waiting, sender) = {
+                let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+                match calls.get(key) {
+                    Some(receiver) => (Some(receiver.clone()), None),
+                    None => {
+                        let (sender, receiver) = watch::channel(None);
+                        calls.insert(key.clone(), receiver);
+                        (None, Some(sender))
+                    }
+                }
+            };
+
+            if let Some(mut receiver) = waiting {
+                log::debug!("Joining in-flight request for {:?}", key);
+                if let Ok(value) = receiver.wait_for(Option::is_some).await {
+                    if let Some(value) = value.as_ref() {
+                        return value.clone();
+                    }
+                }
+                // The leader was cancelled before finishing, so try to take over.
+                continue;
+            }
+
+            if let Some(sender) = sender {
+                let _leader = Leader { flight: self, key };
+                let value = work().await;
+                let _ = sender.send(Some(value.clone()));
+                return value;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+pub(crate) use blocking::BlockingSingleFlight;
+
+#[cfg(feature = "blocking")]
+mod blocking {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Condvar, Mutex};
+
+    use crate::cache::CacheKey;
+
+    #[derive(Debug)]
+    enum State<T> {
+        Pending,
+        Done(T),
+        Abandoned,
+    }
+
+    #[derive(Debug)]
+    struct Call<T> {
+        state: Mutex<State<T>>,
+        ready: Condvar,
+    }
+
+    /// The blocking counterpart of [`super::SingleFlight`], for callers on separate threads.
+    #[derive(Debug)]
+    pub(crate) struct BlockingSingleFlight<T> {
+        calls: Mutex<HashMap<CacheKey, Arc<Call<T>>>>,
+    }
+
+    /// Publishes the result of a call, marking it abandoned if the leader panicked.
+    struct Leader<'a, T> {
+        flight: &'a BlockingSingleFlight<T>,
+        key: &'a CacheKey,
+        call: Arc<Call<T>>,
+    }
+
+    impl<T> Drop for Leader<'_, T> {
+        fn drop(&mut self) {
+            self.flight
+                .calls
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(self.key);
+
+            let mut state = self.call.state.lock().unwrap_or_else(|e| e.into_inner());
+            if matches!(*state, State::Pending) {
+                *state = State::Abandoned;
+            }
+            self.call.ready.notify_all();
+        }
+    }
+
+    impl<T: Clone> BlockingSingleFlight<T> {
+        pub(crate) fn new() -> Self {
+            Self {
+                calls: Mutex::new(HashMap::new()),
+            }
+        }
+
+        /// Runs `work` unless a call with the same key is already in flight, in which case its result is shared.
+        pub(crate) fn run<F>(&self, key: &CacheKey, work: F) -> T
+        where
+            F: FnOnce() -> T,
+        {
+            loop {
+                let (call, leading) = {
+                    let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+                    match calls.get(key) {
+                        Some(call) => (call.clone(), false),
+                        None => {
+                            let call = Arc::new(Call {
+                                state: Mutex::new(State::Pending),
+                                ready: Condvar::new(),
+                            });
+                            calls.insert(key.clone(), call.clone());
+                            (call, true)
+                        }
+                    }
+                };
+
+                if leading {
+                    let leader = Leader {
+                        flight: self,
+                        key,
+                        call,
+                    };
+                    let value = work();
+                    *leader.call.state.lock().unwrap_or_else(|e| e.into_inner()) =
+                        State::Done(value.clone());
+                    return value;
+                }
+
+                log::debug!("Joining in-flight request for {:?}", key);
+                let mut state = call.state.lock().unwrap_or_else(|e| e.into_inner());
+                while matches!(*state, State::Pending) {
+                    state = call.ready.wait(state).unwrap_or_else(|e| e.into_inner());
+                }
+                if let State::Done(value) = &*state {
+                    return value.clone();
+                }
+                // The leader panicked before finishing, so try to take over.
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test_log::test(tokio::test)]
+    async fn test_concurrent_calls_share_one_result() {
+        let flight = SingleFlight::new();
+        let calls = AtomicUsize::new(0);
+        let key = CacheKey::license("1234567890123456");
+
+        let work = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            42
+        };
+
+        let (a, b, c) = tokio::join!(
+            flight.run(&key, work),
+            flight.run(&key, work),
+            flight.run(&key, work)
+        );
+
+        assert_eq!((a, b, c), (42, 42, 42));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        flight.run(&key, work).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test_log::test(tokio::test)]
+    async fn test_cancelled_leader_is_replaced() {
+        let flight = SingleFlight::new();
+        let key = CacheKey::license("1234567890123456");
+
+        let leader = flight.run(&key, std::future::pending::<u32>);
+        let _ = tokio::time::timeout(Duration::from_millis(10), leader).await;
+
+        assert_eq!(flight.run(&key, || async { 7 }).await, 7);
+    }
+}
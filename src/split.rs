@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use log::warn;
+
+use crate::errors::SIAError;
+use crate::models::{LicenseRole, LicenseSector, LicenseState, Query};
+
+/// How many narrowed searches run at once. Every search still goes through the client's rate limiter.
+pub(crate) const SPLIT_CONCURRENCY: usize = 4;
+
+/// The combined results of a search that was split into narrower searches because it matched too many licenses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitSearch {
+    /// Every license found, de-duplicated by license number. Licenses without a readable number are all kept.
+    pub licenses: Vec<LicenseState>,
+    /// Narrowed searches that still matched too many licenses, so their licenses are missing from `licenses`.
+    pub too_broad: Vec<Query>,
+}
+
+impl SplitSearch {
+    /// Checks if every narrowed search completed, so `licenses` holds every match.
+    pub fn is_complete(&self) -> bool {
+        self.too_broad.is_empty()
+    }
+
+    /// Converts into the result a plain search returns, failing with `TooManyResults` if anything is missing.
+    pub(crate) fn into_result(self) -> Result<Vec<LicenseState>, SIAError> {
+        if !self.is_complete() {
+            return Err(SIAError::TooManyResults);
+        }
+
+        if self.licenses.is_empty() {
+            return Err(SIAError::NoLicensesFound);
+        }
+
+        Ok(self.licenses)
+    }
+}
+
+/// Narrows a name search by every role and sector it doesn't already specify.
+/// Returns nothing if it can't be narrowed any further.
+pub(crate) fn sub_queries(query: &Query) -> Vec<Query> {
+    if query.license_number().is_some() {
+        return Vec::new();
+    }
+
+    let roles: Vec<Option<&LicenseRole>> = if is_set(&query.role) {
+        vec![None]
+    } else {
+        LicenseRole::ALL.iter().map(Some).collect()
+    };
+    let sectors: Vec<Option<&LicenseSector>> = if is_set(&query.license_sector) {
+        vec![None]
+    } else {
+        LicenseSector::ALL.iter().map(Some).collect()
+    };
+
+    if roles.len() == 1 && sectors.len() == 1 {
+        return Vec::new();
+    }
+
+    let mut queries = Vec::with_capacity(roles.len() * sectors.len());
+    for role in &roles {
+        for sector in &sectors {
+            let mut narrowed = query.clone();
+            if let Some(role) = role {
+                narrowed = narrowed.with_role((*role).clone());
+            }
+            if let Some(sector) = sector {
+                narrowed = narrowed.with_license_sector((*sector).clone());
+            }
+            queries.push(narrowed);
+        }
+    }
+
+    queries
+}
+
+/// Checks if a query field holds a value, treating a blank one as unset.
+fn is_set(field: &Option<String>) -> bool {
+    field
+        .as_deref()
+        .is_some_and(|value| !value.trim().is_empty())
+}
+
+/// Combines the results of narrowed searches.
+///
+/// Searches that found nothing are skipped, and any other error fails the whole search.
+pub(crate) fn merge(
+    results: impl IntoIterator<Item = (Query, Result<Vec<LicenseState>, SIAError>)>,
+) -> Result<SplitSearch, SIAError> {
+    let mut licenses = BTreeMap::new();
+    let mut unnumbered = Vec::new();
+    let mut too_broad = Vec::new();
+
+    for (query, result) in results {
+        match result {
+            Ok(found) => {
+                for license in found {
+                    // Licenses without a readable number can't be told apart, so keep them all.
+                    match license.license_number.clone() {
+                        Some(number) => {
+                            licenses.entry(number).or_insert(license);
+                        }
+                        None => unnumbered.push(license),
+                    }
+                }
+            }
+            Err(SIAError::NoLicensesFound) => {}
+            Err(SIAError::TooManyResults) => {
+                warn!("Narrowed search is still too broad: {:?}", query);
+                too_broad.push(query);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(SplitSearch {
+        licenses: licenses.into_values().chain(unnumbered).collect(),
+        too_broad,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sub_queries_cover_unset_fields() {
+        let query = Query::new().with_last_name("Smith".to_string());
+        assert_eq!(
+            sub_queries(&query).len(),
+            LicenseRole::ALL.len() * LicenseSector::ALL.len()
+        );
+
+        let blank = Query {
+            role: Some(" ".to_string()),
+            license_sector: Some(String::new()),
+            ..query.clone()
+        };
+        assert_eq!(
+            sub_queries(&blank).len(),
+            LicenseRole::ALL.len() * LicenseSector::ALL.len()
+        );
+
+        let query = query.with_role(LicenseRole::Frontline);
+        let narrowed = sub_queries(&query);
+        assert_eq!(narrowed.len(), LicenseSector::ALL.len());
+        assert!(narrowed
+            .iter()
+            .all(|q| q.role.as_deref() == Some(LicenseRole::Frontline.form_value())));
+
+        let query = query.with_license_sector(LicenseSector::DoorSupervision);
+        assert!(sub_queries(&query).is_empty());
+    }
+
+    #[test]
+    fn test_merge_dedupes_and_reports_broad_queries() {
+        let license = |number: &str| LicenseState {
+            license_number: number.parse().ok(),
+            raw_license_number: number.to_string(),
+            ..LicenseState::example()
+        };
+        let query = Query::new().with_last_name("Smith".to_string());
+
+        let split = merge(vec![
+            (
+                query.clone(),
+                Ok(vec![
+                    license("2222222222222222"),
+                    license("1111111111111111"),
+                ]),
+            ),
+            (query.clone(), Ok(vec![license("1111111111111111")])),
+            (query.clone(), Err(SIAError::NoLicensesFound)),
+            (query.clone(), Err(SIAError::TooManyResults)),
+        ])
+        .unwrap();
+
+        assert_eq!(split.licenses.len(), 2);
+        assert_eq!(split.licenses[0].license_number_str(), "1111111111111111");
+        assert_eq!(split.too_broad.len(), 1);
+        assert!(matches!(split.into_result(), Err(SIAError::TooManyResults)));
+
+        let split = merge(vec![
+            (query.clone(), Ok(vec![license("")])),
+            (
+                query.clone(),
+                Ok(vec![license(""), license("1111111111111111")]),
+            ),
+        ])
+        .unwrap();
+        assert_eq!(split.licenses.len(), 3);
+        assert_eq!(split.licenses[0].license_number_str(), "1111111111111111");
+
+        assert!(matches!(
+            merge(vec![(query, Err(SIAError::ServiceUnavailable))]),
+            Err(SIAError::ServiceUnavailable)
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::cache::normalise_license;
+use crate::transport::{Transport, TransportError, TransportResponse};
+
+/// A transport that serves stored pages instead of contacting the register, for offline tests.
+///
+/// Queued responses are served first, in order, whatever the request. After that, requests are matched
+/// on their `LicenseNo` or `Surname` form field, and anything unmatched gets the default response,
+/// which is a `404` unless set otherwise. Every request is recorded so tests can inspect what was sent.
+///
+/// Cloning the transport shares its responses and recorded requests.
+///
+/// # Example
+///
+/// ```
+/// use sia_rs::{FixtureTransport, SiaClient, TransportResponse};
+///
+/// let transport = FixtureTransport::new()
+///     .with_license("1234 5678 9012 3456", TransportResponse::ok("<p>No results found</p>"));
+/// let client = SiaClient::builder().transport(transport.clone()).build().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixtureTransport {
+    state: Arc<Mutex<State>>,
+}
+
+/// A request received by a [`FixtureTransport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureRequest {
+    pub url: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl FixtureRequest {
+    /// Returns the value of a form field.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+enum Matcher {
+    License(String),
+    Surname(String),
+}
+
+#[derive(Debug)]
+struct State {
+    queued: VecDeque<Result<TransportResponse, TransportError>>,
+    routes: Vec<(Matcher, TransportResponse)>,
+    fallback: TransportResponse,
+    requests: Vec<FixtureRequest>,
+}
+
+impl Default for FixtureTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixtureTransport {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                queued: VecDeque::new(),
+                routes: Vec::new(),
+                fallback: TransportResponse::new(404, ""),
+                requests: Vec::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Serves `response` to searches for the given license number, ignoring spaces and dashes as the client does.
+    pub fn with_license(self, license_no: &str, response: TransportResponse) -> Self {
+        self.lock()
+            .routes
+            .push((Matcher::License(normalise_license(license_no)), response));
+        self
+    }
+
+    /// Serves `response` to searches for the given surname, ignoring case.
+    pub fn with_surname(self, surname: &str, response: TransportResponse) -> Self {
+        self.lock()
+            .routes
+            .push((Matcher::Surname(surname.trim().to_lowercase()), response));
+        self
+    }
+
+    /// Sets the response for requests that match nothing else.
+    pub fn with_default(self, response: TransportResponse) -> Self {
+        self.lock().fallback = response;
+        self
+    }
+
+    /// Queues a response to be served to the next request, ahead of any matching.
+    pub fn push_response(&self, response: TransportResponse) {
+        self.lock().queued.push_back(Ok(response));
+    }
+
+    /// Queues a failure to be returned for the next request, ahead of any matching.
+    pub fn push_error(&self, error: TransportError) {
+        self.lock().queued.push_back(Err(error));
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<FixtureRequest> {
+        self.lock().requests.clone()
+    }
+
+    pub(crate) fn respond(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError> {
+        let request = FixtureRequest {
+            url: url.to_string(),
+            params: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+
+        let mut state = self.lock();
+        state.requests.push(request.clone());
+
+        if let Some(queued) = state.queued.pop_front() {
+            return queued;
+        }
+
+        let license_no = request.param("LicenseNo").map(normalise_license);
+        let surname = request.param("Surname").map(|s| s.trim().to_lowercase());
+        let matched = state.routes.iter().find(|(matcher, _)| match matcher {
+            Matcher::License(expected) => license_no.as_deref() == Some(expected.as_str()),
+            Matcher::Surname(expected) => surname.as_deref() == Some(expected.as_str()),
+        });
+
+        Ok(matched
+            .map(|(_, response)| response.clone())
+            .unwrap_or_else(|| state.fallback.clone()))
+    }
+}
+
+#[async_trait]
+impl Transport for FixtureTransport {
+    async fn post_form(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError> {
+        self.respond(url, params)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl crate::blocking::Transport for FixtureTransport {
+    fn post_form(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError> {
+        self.respond(url, params)
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use crate::transport::{Transport, TransportError, TransportResponse};
+
+/// The default transport, sending requests with a pooled [`reqwest::Client`].
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn post_form(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError> {
+        let res = self.client.post(url).form(params).send().await?;
+
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    name.as_str().to_lowercase(),
+                    value.to_str().ok()?.to_string(),
+                ))
+            })
+            .collect();
+        let body = res.text().await?;
+
+        Ok(TransportResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
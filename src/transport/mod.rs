@@ -0,0 +1,121 @@
+pub use fixture::{FixtureRequest, FixtureTransport};
+pub use http::ReqwestTransport;
+
+mod fixture;
+mod http;
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+/// The HTTP layer used to talk to the register: a form POST goes in, a status and body come out.
+///
+/// The crate ships with [`ReqwestTransport`], used by default, and [`FixtureTransport`], which serves
+/// stored pages for offline tests. Implement this trait to route requests through anything else.
+#[async_trait]
+pub trait Transport: Debug + Send + Sync {
+    /// Sends `params` as a url-encoded form to `url`.
+    async fn post_form(
+        &self,
+        url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<TransportResponse, TransportError>;
+}
+
+/// A response from the register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The response headers, with lower-cased names.
+    pub headers: HashMap<String, String>,
+    /// The response body.
+    pub body: String,
+}
+
+impl TransportResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: HashMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A `200 OK` response with the given body.
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::new(200, body)
+    }
+
+    /// Adds a header to the response.
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.insert(name.to_lowercase(), value.into());
+        self
+    }
+
+    /// Returns the value of a header, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(String::as_str)
+    }
+
+    /// Checks if the status code is in the 2xx range.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Broadly what went wrong when a request couldn't be completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// The connection couldn't be established.
+    Connect,
+    /// The request timed out.
+    Timeout,
+    /// The response body couldn't be read.
+    Body,
+    /// Any other failure.
+    Other,
+}
+
+/// A request that failed before a response was received.
+#[derive(Error, Debug, Clone)]
+#[error("{message}")]
+pub struct TransportError {
+    pub kind: TransportErrorKind,
+    pub message: String,
+    #[source]
+    pub source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            source: None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(err: reqwest::Error) -> Self {
+        let kind = if err.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if err.is_connect() || err.is_request() {
+            TransportErrorKind::Connect
+        } else if err.is_body() || err.is_decode() {
+            TransportErrorKind::Body
+        } else {
+            TransportErrorKind::Other
+        };
+
+        Self {
+            kind,
+            message: err.to_string(),
+            source: Some(Arc::new(err)),
+        }
+    }
+}
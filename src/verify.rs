@@ -0,0 +1,273 @@
+use std::fmt::Display;
+
+use crate::models::{LicenseNumber, LicenseRole, LicenseSector, LicenseState};
+
+/// The details a person has given about their license, to be checked against the register.
+///
+/// The register doesn't show dates of birth, so only the name and, optionally, the sector and role are checked.
+///
+/// # Example
+///
+/// ```no_run
+/// use sia_rs::{Candidate, LicenseSector, SiaClient, Verdict};
+///
+/// # async fn run() -> Result<(), sia_rs::SIAError> {
+/// let candidate = Candidate::new("1234 5678 9012 3456".parse().unwrap(), "John", "Smith")
+///     .with_sector(LicenseSector::DoorSupervision);
+///
+/// let verification = SiaClient::new().verify(&candidate).await?;
+/// if verification.verdict != Verdict::Match {
+///     println!("Check failed: {:?}", verification.reasons);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub license_number: LicenseNumber,
+    pub first_name: String,
+    pub last_name: String,
+    pub sector: Option<LicenseSector>,
+    pub role: Option<LicenseRole>,
+}
+
+/// The overall result of checking a [`Candidate`] against the register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The license exists and every supplied detail matches it.
+    Match,
+    /// The license exists but is held by someone with a different name.
+    NameMismatch,
+    /// The license exists and the name matches, but it is for a different sector.
+    SectorMismatch,
+    /// The license exists and the name and sector match, but it is for a different role.
+    RoleMismatch,
+    /// The register has no license with that number.
+    NotFound,
+}
+
+/// A detail that didn't match the register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    FirstName {
+        expected: String,
+        found: String,
+    },
+    LastName {
+        expected: String,
+        found: String,
+    },
+    Sector {
+        expected: LicenseSector,
+        found: LicenseSector,
+    },
+    Role {
+        expected: LicenseRole,
+        found: LicenseRole,
+    },
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mismatch::FirstName { expected, found } => {
+                write!(
+                    f,
+                    "first name is {:?} on the register, not {:?}",
+                    found, expected
+                )
+            }
+            Mismatch::LastName { expected, found } => {
+                write!(
+                    f,
+                    "last name is {:?} on the register, not {:?}",
+                    found, expected
+                )
+            }
+            Mismatch::Sector { expected, found } => {
+                write!(f, "sector is {} on the register, not {}", found, expected)
+            }
+            Mismatch::Role { expected, found } => {
+                write!(f, "role is {} on the register, not {}", found, expected)
+            }
+        }
+    }
+}
+
+/// The result of checking a [`Candidate`] against the register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub verdict: Verdict,
+    /// Every detail that didn't match. Empty for a match or when the license wasn't found.
+    pub reasons: Vec<Mismatch>,
+    /// The license the candidate was compared with, if one was found. Check its status before relying on it.
+    pub license: Option<LicenseState>,
+}
+
+impl Candidate {
+    pub fn new(
+        license_number: LicenseNumber,
+        first_name: impl Into<String>,
+        last_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            license_number,
+            first_name: first_name.into(),
+            last_name: last_name.into(),
+            sector: None,
+            role: None,
+        }
+    }
+
+    /// Also checks the license is for the given sector.
+    pub fn with_sector(mut self, sector: LicenseSector) -> Self {
+        self.sector = Some(sector);
+        self
+    }
+
+    /// Also checks the license is for the given role.
+    pub fn with_role(mut self, role: LicenseRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// Compares the candidate with the licenses returned for their license number,
+    /// picking the license with the fewest mismatches.
+    pub fn check(&self, licenses: &[LicenseState]) -> Verification {
+        let best = licenses
+            .iter()
+            .map(|license| (license, self.mismatches(license)))
+            .min_by_key(|(_, mismatches)| mismatches.len());
+
+        let Some((license, reasons)) = best else {
+            return Verification {
+                verdict: Verdict::NotFound,
+                reasons: Vec::new(),
+                license: None,
+            };
+        };
+
+        let verdict = match reasons.first() {
+            None => Verdict::Match,
+            Some(Mismatch::FirstName { .. } | Mismatch::LastName { .. }) => Verdict::NameMismatch,
+            Some(Mismatch::Sector { .. }) => Verdict::SectorMismatch,
+            Some(Mismatch::Role { .. }) => Verdict::RoleMismatch,
+        };
+
+        Verification {
+            verdict,
+            reasons,
+            license: Some(license.clone()),
+        }
+    }
+
+    /// Lists the details that differ from a license, most serious first.
+    fn mismatches(&self, license: &LicenseState) -> Vec<Mismatch> {
+        let mut mismatches = Vec::new();
+
+        if !first_name_matches(&self.first_name, &license.first_name) {
+            mismatches.push(Mismatch::FirstName {
+                expected: self.first_name.clone(),
+                found: license.first_name.clone(),
+            });
+        }
+
+        if normalise_name(&self.last_name) != normalise_name(&license.last_name) {
+            mismatches.push(Mismatch::LastName {
+                expected: self.last_name.clone(),
+                found: license.last_name.clone(),
+            });
+        }
+
+        if let Some(sector) = self
+            .sector
+            .as_ref()
+            .filter(|sector| **sector != license.sector)
+        {
+            mismatches.push(Mismatch::Sector {
+                expected: sector.clone(),
+                found: license.sector.clone(),
+            });
+        }
+
+        if let Some(role) = self.role.as_ref().filter(|role| **role != license.role) {
+            mismatches.push(Mismatch::Role {
+                expected: role.clone(),
+                found: license.role.clone(),
+            });
+        }
+
+        mismatches
+    }
+}
+
+/// Lower-cases a name and drops punctuation, so "O'Brien" matches "OBRIEN".
+fn normalise_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(|part| {
+            part.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Checks a first name, allowing the register to also show middle names after it.
+fn first_name_matches(expected: &str, found: &str) -> bool {
+    let expected = normalise_name(expected);
+    let found = normalise_name(found);
+
+    !expected.is_empty()
+        && (found == expected
+            || found
+                .strip_prefix(&expected)
+                .is_some_and(|rest| rest.starts_with(' ')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(first_name: &str, last_name: &str, sector: LicenseSector) -> LicenseState {
+        LicenseState {
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            sector,
+            ..LicenseState::example()
+        }
+    }
+
+    #[test]
+    fn test_verdicts() {
+        let candidate = Candidate::new("1234567890123456".parse().unwrap(), "john", "O'Brien")
+            .with_sector(LicenseSector::DoorSupervision);
+
+        let found = [license(
+            "JOHN PAUL",
+            "OBRIEN",
+            LicenseSector::DoorSupervision,
+        )];
+        assert_eq!(candidate.check(&found).verdict, Verdict::Match);
+
+        let found = [license("JOHNNY", "OBRIEN", LicenseSector::DoorSupervision)];
+        let verification = candidate.check(&found);
+        assert_eq!(verification.verdict, Verdict::NameMismatch);
+        assert!(matches!(
+            verification.reasons[..],
+            [Mismatch::FirstName { .. }]
+        ));
+
+        let found = [
+            license("JOHN", "OBRIEN", LicenseSector::SecurityGuard),
+            license("JANE", "OBRIEN", LicenseSector::SecurityGuard),
+        ];
+        let verification = candidate.check(&found);
+        assert_eq!(verification.verdict, Verdict::SectorMismatch);
+        assert_eq!(verification.license.unwrap().first_name, "JOHN");
+
+        assert_eq!(candidate.check(&[]).verdict, Verdict::NotFound);
+    }
+}